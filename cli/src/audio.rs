@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write, stdout};
+use std::path::Path;
+use std::time::Duration;
+
+use common::audio::{AudioSink, Sequence, SequencePlayer};
+
+/// Rings the terminal bell at the start of every sound, the closest a terminal gets to a buzzer
+pub struct BellSink;
+
+impl AudioSink for BellSink {
+    fn play(&mut self, sequence: Sequence) {
+        if !sequence.is_empty() {
+            let mut out = stdout();
+            let _ = out.write_all(b"\x07").and_then(|_| out.flush());
+        }
+    }
+
+    fn update(&mut self, _elapsed: Duration) {}
+}
+
+const SAMPLE_RATE: u32 = 22_050;
+
+/// Records the sound track of the whole session as a square wave, like the buzzer plays it, streaming
+/// it to a WAV file. The file is complete once [`WavSink::finish`] is called.
+pub struct WavSink {
+    out: BufWriter<File>,
+    player: SequencePlayer,
+    /// The number of 8-bit unsigned mono samples written so far
+    written: u32,
+    /// Silent samples not written yet, dropped if nothing is played after them
+    silence: u32,
+    /// Position within the current period of the wave, from 0 to 1
+    phase: f32,
+    /// Time passed that was too short for a whole sample
+    remainder: Duration,
+    /// The first error writing the file, after which nothing more is written
+    error: Option<io::Error>,
+}
+
+impl WavSink {
+    /// Creates the file and writes the header, with the sizes filled in by [`WavSink::finish`]
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        write_header(&mut out, 0)?;
+
+        Ok(Self {
+            out,
+            player: SequencePlayer::new(),
+            written: 0,
+            silence: 0,
+            phase: 0.0,
+            remainder: Duration::ZERO,
+            error: None,
+        })
+    }
+
+    /// Completes the file, reporting the first error that happened while recording
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+
+        self.out.seek(SeekFrom::Start(0))?;
+        write_header(&mut self.out, self.written)?;
+        self.out.flush()
+    }
+
+    fn write_samples(&mut self, samples: &[u8]) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let mut silence = io::repeat(128).take(self.silence as u64);
+        let result =
+            io::copy(&mut silence, &mut self.out).and_then(|_| self.out.write_all(samples));
+        self.written = self
+            .written
+            .saturating_add(self.silence)
+            .saturating_add(samples.len() as u32);
+        self.silence = 0;
+        self.error = result.err();
+    }
+}
+
+fn write_header(out: &mut impl Write, data_len: u32) -> io::Result<()> {
+    out.write_all(b"RIFF")?;
+    out.write_all(&data_len.saturating_add(36).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&1u16.to_le_bytes())?; // mono
+    out.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    out.write_all(&SAMPLE_RATE.to_le_bytes())?; // bytes per second
+    out.write_all(&1u16.to_le_bytes())?; // bytes per frame
+    out.write_all(&8u16.to_le_bytes())?; // bits per sample
+
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())
+}
+
+impl AudioSink for WavSink {
+    fn play(&mut self, sequence: Sequence) {
+        self.player.start(sequence);
+    }
+
+    fn update(&mut self, elapsed: Duration) {
+        let frequency = self.player.update(elapsed) as f32;
+
+        let time = self.remainder + elapsed;
+        let count = (time.as_secs_f64() * SAMPLE_RATE as f64) as u32;
+        self.remainder = time.saturating_sub(Duration::from_secs(count as u64) / SAMPLE_RATE);
+
+        if frequency == 0.0 {
+            self.silence = self.silence.saturating_add(count);
+            return;
+        }
+
+        let samples: Vec<u8> = (0..count)
+            .map(|_| {
+                let sample = if self.phase < 0.5 { 160 } else { 96 };
+                self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+                sample
+            })
+            .collect();
+        self.write_samples(&samples);
+    }
+}
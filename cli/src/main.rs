@@ -1,16 +1,27 @@
+mod audio;
+mod storage;
+
 use chrono::Local;
 use common::menu::GameMenu;
-use log::{Level, LevelFilter, Metadata, Record, info};
+use log::{Level, LevelFilter, Metadata, Record, debug, info, warn};
 use std::collections::VecDeque;
 use std::fmt::Display;
 use std::io::{self, stdout};
 use std::sync::Mutex;
 
+use audio::{BellSink, WavSink};
+use common::Game;
+use common::audio::AudioSink;
 use common::display::{Pixel, PixelDisplay};
-use common::input::{BasicInput, DebouncedInput};
+use common::framebuffer::Framebuffer;
+use common::game_loop::{GameLoop, TimeSource};
+use common::input::{
+    Button, Controller, Input, InputEvent, InputEventBuffer, InputEventKind, Players,
+};
+use common::random::Pcg32;
+use common::shell::GameShell;
 use common::snake::SnakeGame;
 use common::tetris::TetrisGame;
-use common::{Game, RandomNumberSource};
 use crossterm::event::{
     KeyEventKind, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
     PushKeyboardEnhancementFlags, poll,
@@ -23,28 +34,49 @@ use crossterm::{
     execute, queue,
     terminal::{disable_raw_mode, enable_raw_mode},
 };
-use rand::prelude::*;
 use std::time::{Duration, Instant};
+use storage::FileStorage;
 
 const HELP: &str = r#"
  - Use WASD + Space to play
- - Use Esc to quit
+ - A second player uses the arrow keys + Enter
+ - Press P (or W and S together) to pause a running game
+ - Press S in the game selection to see the high scores, W for the settings
+ - Use Esc to quit, a running game is saved and continues on the next start
+ - Use --seed <number> to replay a previous run
+ - Use --wav <path> to record the sound effects to a file instead of ringing the terminal bell
 "#;
 
+/// Returns the value following the argument `name`, if given
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// The wall clock, counting from the start of the program
+struct Clock(Instant);
+
+impl TimeSource for Clock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
 struct ConsoleDisplay<const ROWS: usize, const COLS: usize> {
-    buffer: [[Pixel; COLS]; ROWS],
+    buffer: Framebuffer<ROWS, COLS>,
 }
 
 impl<const ROWS: usize, const COLS: usize> ConsoleDisplay<ROWS, COLS> {
     pub fn new() -> Self {
         Self {
-            buffer: [[Pixel::Off; COLS]; ROWS],
+            buffer: Framebuffer::new(),
         }
     }
-
-    pub fn changed(&self, other: &Self) -> bool {
-        self.buffer == other.buffer
-    }
 }
 
 impl<const ROWS: usize, const COLS: usize> Display for ConsoleDisplay<ROWS, COLS> {
@@ -61,7 +93,10 @@ impl<const ROWS: usize, const COLS: usize> Display for ConsoleDisplay<ROWS, COLS
                 write!(
                     f,
                     "{}",
-                    match (self.buffer[row][col], self.buffer[row + 1][col]) {
+                    match (
+                        self.buffer.get_pixel(row, col),
+                        self.buffer.get_pixel(row + 1, col)
+                    ) {
                         (Pixel::On, Pixel::On) => "█",
                         (Pixel::On, Pixel::Off) => "▀",
                         (Pixel::Off, Pixel::On) => "▄",
@@ -82,21 +117,16 @@ impl<const ROWS: usize, const COLS: usize> Display for ConsoleDisplay<ROWS, COLS
 }
 
 impl<const ROWS: usize, const COLS: usize> PixelDisplay for ConsoleDisplay<ROWS, COLS> {
-    const ROWS: usize = ROWS;
-    const COLUMNS: usize = COLS;
-
-    fn set_pixel(&mut self, row: usize, col: usize, value: Pixel) {
-        self.buffer[row][col] = value;
+    fn rows(&self) -> usize {
+        ROWS
     }
-}
 
-struct Random {
-    rng: ThreadRng,
-}
+    fn columns(&self) -> usize {
+        COLS
+    }
 
-impl RandomNumberSource for Random {
-    fn next_u32(&mut self) -> u32 {
-        self.rng.random()
+    fn set_pixel(&mut self, row: usize, col: usize, value: Pixel) {
+        self.buffer.set_pixel(row, col, value);
     }
 }
 
@@ -157,62 +187,130 @@ impl Display for ConsoleLogger {
     }
 }
 
+/// Maps a key to the player and button it controls
+fn key_to_button(code: KeyCode) -> Option<(usize, Button)> {
+    match code {
+        KeyCode::Char('w') => Some((0, Button::Up)),
+        KeyCode::Char('a') => Some((0, Button::Left)),
+        KeyCode::Char('s') => Some((0, Button::Down)),
+        KeyCode::Char('d') => Some((0, Button::Right)),
+        KeyCode::Char(' ') => Some((0, Button::Action)),
+        KeyCode::Up => Some((1, Button::Up)),
+        KeyCode::Left => Some((1, Button::Left)),
+        KeyCode::Down => Some((1, Button::Down)),
+        KeyCode::Right => Some((1, Button::Right)),
+        KeyCode::Enter => Some((1, Button::Action)),
+        _ => None,
+    }
+}
+
+const PLAYERS: usize = 2;
 const ROWS: usize = 42;
 const COLS: usize = 16;
-fn print_events() -> io::Result<()> {
+fn print_events(seed: u64, audio: &mut dyn AudioSink) -> io::Result<()> {
     let mut d: ConsoleDisplay<ROWS, COLS> = ConsoleDisplay::new();
-    let mut d2: ConsoleDisplay<ROWS, COLS> = ConsoleDisplay::new(); // for double buffering
 
-    let mut i = BasicInput::default();
-    let mut i_debounced = DebouncedInput::default();
-    let mut rng = Random { rng: rand::rng() };
+    let mut game_loop = GameLoop::new(Clock(Instant::now()));
+    let mut events = [InputEventBuffer::<32>::new(); PLAYERS];
+    let mut players = Players::<Controller, PLAYERS>::default();
+    let mut rng = Pcg32::new(seed);
 
     let mut games = [
-        &mut TetrisGame::<ROWS, COLS>::new() as &mut dyn Game<_, _, _>,
-        &mut SnakeGame::<ROWS, COLS>::new() as &mut dyn Game<_, _, _>,
+        &mut GameShell::new(TetrisGame::new()) as &mut dyn Game<_, _>,
+        &mut GameShell::new(SnakeGame::new()) as &mut dyn Game<_, _>,
     ];
 
     let mut game = GameMenu::new(&mut games);
 
-    let mut last_frame_time = Instant::now();
-    loop {
-        // Wait up to 10ms for another event
-        if poll(Duration::from_millis(10))? {
-            // It's guaranteed that read() won't block if `poll` returns `Ok(true)`
-            let event = read()?;
-
-            if let Event::Key(ke) = event {
-                match (ke.code, ke.kind) {
-                    (KeyCode::Char('w'), KeyEventKind::Press) => i.up = true,
-                    (KeyCode::Char('a'), KeyEventKind::Press) => i.left = true,
-                    (KeyCode::Char('s'), KeyEventKind::Press) => i.down = true,
-                    (KeyCode::Char('d'), KeyEventKind::Press) => i.right = true,
-                    (KeyCode::Char('w'), KeyEventKind::Release) => i.up = false,
-                    (KeyCode::Char('a'), KeyEventKind::Release) => i.left = false,
-                    (KeyCode::Char('s'), KeyEventKind::Release) => i.down = false,
-                    (KeyCode::Char('d'), KeyEventKind::Release) => i.right = false,
-                    (KeyCode::Char(' '), KeyEventKind::Press) => i.action = true,
-                    (KeyCode::Char(' '), KeyEventKind::Release) => i.action = false,
-
-                    (KeyCode::Esc, _) => break,
-                    _ => {}
+    let mut storage = FileStorage::in_data_dir();
+    if let Err(e) = game.load_high_scores(&mut storage) {
+        warn!("could not load the high scores: {:?}", e);
+    }
+    if let Err(e) = game.load_settings(&mut storage) {
+        warn!("could not load the settings: {:?}", e);
+    }
+    match game.resume_saved_game(&mut storage) {
+        Ok(true) => info!("Continuing the saved {} game", game.name()),
+        Ok(false) => {}
+        Err(e) => warn!("could not continue the saved game: {:?}", e),
+    }
+
+    'main: loop {
+        // wait for another event until the next tick is due
+        if poll(game_loop.until_next_tick())? {
+            // read all available events so that quick presses keep their order and timing
+            loop {
+                // It's guaranteed that read() won't block if `poll` returns `Ok(true)`
+                if let Event::Key(ke) = read()? {
+                    if ke.code == KeyCode::Esc {
+                        if let Err(e) = game.save_game(&mut storage) {
+                            warn!("could not save the game: {:?}", e);
+                        }
+                        break 'main;
+                    }
+
+                    if ke.code == KeyCode::Char('p') && ke.kind == KeyEventKind::Press {
+                        game.pause(&d);
+                    }
+
+                    let kind = match ke.kind {
+                        KeyEventKind::Press => Some(InputEventKind::Pressed),
+                        KeyEventKind::Release => Some(InputEventKind::Released),
+                        KeyEventKind::Repeat => None,
+                    };
+
+                    if let (Some((player, button)), Some(kind)) = (key_to_button(ke.code), kind) {
+                        events[player].push(InputEvent {
+                            button,
+                            kind,
+                            timestamp: game_loop.time().now(),
+                        });
+                    }
+                }
+
+                if !poll(Duration::ZERO)? {
+                    break;
                 }
             }
         }
 
-        let current_time = Instant::now();
-        let elapsed = current_time - last_frame_time;
-
-        if elapsed > Duration::from_millis(10) {
-            last_frame_time = current_time;
+        let ticks = game_loop.advance();
+        if ticks > 0 {
+            let tick = game_loop.tick_duration();
+            for _ in 0..ticks {
+                // the events are applied with the first tick, the others see no new presses
+                for (index, (controller, events)) in
+                    players.iter_mut().zip(events.iter_mut()).enumerate()
+                {
+                    controller.update(events.take().as_slice(), tick);
+                    for gesture in controller.gestures() {
+                        debug!("player {}: {:?}", index, gesture);
+                    }
+                }
 
-            // double buffering to only update if the display actually changed...
-            i_debounced.update(&i);
+                game.update(tick, &players, &d, &mut rng);
+                for event in game.events() {
+                    debug!("{:?}", event);
+                }
+                if game.settings().sound {
+                    audio.play_events(game.events());
+                }
+                audio.update(tick);
+            }
 
-            game.update(elapsed, &i_debounced, &mut d, &mut rng);
+            if let Err(e) = game.save_high_scores(&mut storage) {
+                warn!("could not save the high scores: {:?}", e);
+            }
+            if let Err(e) = game.save_settings(&mut storage) {
+                warn!("could not save the settings: {:?}", e);
+            }
+            if let Err(e) = game.discard_saved_game(&mut storage) {
+                warn!("could not remove the saved game: {:?}", e);
+            }
 
-            // update display only if contents changed
-            if d.changed(&d2) {
+            // only redraw the terminal if something moved
+            if game.is_dirty() {
+                game.render(&mut d);
                 execute!(
                     stdout(),
                     terminal::BeginSynchronizedUpdate,
@@ -225,9 +323,6 @@ fn print_events() -> io::Result<()> {
                     terminal::EndSynchronizedUpdate
                 )?;
             }
-
-            // swap the buffers
-            std::mem::swap(&mut d, &mut d2);
         }
     }
 
@@ -239,6 +334,25 @@ fn main() -> io::Result<()> {
     log::set_max_level(LevelFilter::Debug);
     info!("{}", HELP);
 
+    let seed = arg_value("--seed")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(rand::random);
+    info!("Using random seed {}", seed);
+
+    let wav_path = arg_value("--wav");
+    let mut wav = match &wav_path {
+        Some(path) => {
+            info!("Recording the sound effects to {}", path);
+            Some(WavSink::create(path)?)
+        }
+        None => None,
+    };
+    let mut bell = BellSink;
+    let audio: &mut dyn AudioSink = match wav.as_mut() {
+        Some(wav) => wav,
+        None => &mut bell,
+    };
+
     //println!("{}", CONSOLE_LOGGER);
 
     //return Ok(());
@@ -266,7 +380,7 @@ fn main() -> io::Result<()> {
         )?;
     }
 
-    if let Err(e) = print_events() {
+    if let Err(e) = print_events(seed, audio) {
         println!("Error: {:?}\r", e);
     }
 
@@ -274,6 +388,15 @@ fn main() -> io::Result<()> {
         execute!(stdout, PopKeyboardEnhancementFlags)?;
     }
     execute!(stdout, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+
+    // reported only now, the log is not shown once the alternate screen is left
+    if let (Some(wav), Some(path)) = (wav, wav_path) {
+        match wav.finish() {
+            Ok(()) => eprintln!("Wrote the sound track to {}", path),
+            Err(e) => eprintln!("Could not write the sound track to {}: {}", path, e),
+        }
+    }
 
-    disable_raw_mode()
+    Ok(())
 }
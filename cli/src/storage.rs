@@ -0,0 +1,68 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use common::storage::{Storage, StorageError};
+use log::warn;
+
+/// Keeps each record in its own file, at `<root>/<namespace>/<name>`
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Storage in the user's data directory, `$XDG_DATA_HOME/flipdot` or `~/.local/share/flipdot`,
+    /// falling back to the current directory
+    pub fn in_data_dir() -> Self {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+            .unwrap_or_default();
+
+        Self::new(data_home.join("flipdot"))
+    }
+
+    fn path(&self, namespace: &str, name: &str) -> PathBuf {
+        self.root.join(namespace).join(name)
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(
+        &mut self,
+        namespace: &str,
+        name: &str,
+        buffer: &mut [u8],
+    ) -> Result<Option<usize>, StorageError> {
+        let data = match std::fs::read(self.path(namespace, name)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                warn!("could not read {}/{}: {}", namespace, name, e);
+                return Err(StorageError::Io);
+            }
+        };
+
+        let target = buffer.get_mut(..data.len()).ok_or(StorageError::TooLarge)?;
+        target.copy_from_slice(&data);
+        Ok(Some(data.len()))
+    }
+
+    fn write(&mut self, namespace: &str, name: &str, data: &[u8]) -> Result<(), StorageError> {
+        let path = self.path(namespace, name);
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, data));
+
+        result.map_err(|e| {
+            warn!("could not write {}: {}", path.display(), e);
+            StorageError::Io
+        })
+    }
+}
@@ -0,0 +1,150 @@
+//! Simple sound effects: sequences of beeps and sweeps that frontends play on whatever makes noise,
+//! e.g. a buzzer.
+
+use core::time::Duration;
+
+use crate::events::{Cue, GameEvent};
+
+/// A single step of a sound, sweeping linearly from one frequency to another. A frequency of zero is
+/// silence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tone {
+    /// Frequency at the start in Hz
+    pub from: u16,
+    /// Frequency at the end in Hz
+    pub to: u16,
+    pub duration: Duration,
+}
+
+impl Tone {
+    pub const fn beep(frequency: u16, millis: u64) -> Self {
+        Self::sweep(frequency, frequency, millis)
+    }
+
+    pub const fn sweep(from: u16, to: u16, millis: u64) -> Self {
+        Self {
+            from,
+            to,
+            duration: Duration::from_millis(millis),
+        }
+    }
+
+    pub const fn rest(millis: u64) -> Self {
+        Self::beep(0, millis)
+    }
+
+    /// The frequency `time` after the start of the tone
+    pub fn frequency_at(&self, time: Duration) -> u16 {
+        if self.duration.is_zero() || time >= self.duration {
+            return self.to;
+        }
+
+        let progress = time.as_micros() as i64;
+        let total = self.duration.as_micros() as i64;
+        let delta = self.to as i64 - self.from as i64;
+        (self.from as i64 + delta * progress / total) as u16
+    }
+}
+
+/// A sound effect or short melody, played one tone after the other
+pub type Sequence = &'static [Tone];
+
+pub const START: Sequence = &[
+    Tone::beep(523, 80),
+    Tone::beep(659, 80),
+    Tone::beep(784, 80),
+    Tone::beep(1047, 160),
+];
+
+pub const EAT: Sequence = &[Tone::beep(1319, 40)];
+
+pub const CLEAR: Sequence = &[Tone::sweep(440, 1760, 150), Tone::beep(1760, 50)];
+
+pub const DIE: Sequence = &[
+    Tone::beep(392, 120),
+    Tone::rest(30),
+    Tone::sweep(330, 110, 400),
+];
+
+impl Cue {
+    pub fn sound(&self) -> Sequence {
+        match self {
+            Cue::Start => START,
+            Cue::Eat => EAT,
+            Cue::Clear => CLEAR,
+            Cue::Die => DIE,
+        }
+    }
+}
+
+/// Trait for system-specific sound output
+pub trait AudioSink {
+    /// Starts playing a sequence, replacing the one currently playing
+    fn play(&mut self, sequence: Sequence);
+
+    /// Advances the playback, called regularly (e.g. after every update of the game)
+    fn update(&mut self, elapsed: Duration);
+
+    /// Plays the sounds asked for by the events of an update, the last one wins
+    fn play_events(&mut self, events: &[GameEvent]) {
+        for event in events {
+            if let GameEvent::SoundCue(cue) = event {
+                self.play(cue.sound());
+            }
+        }
+    }
+}
+
+/// An [`AudioSink`] that stays silent, e.g. when the sound is turned off
+pub struct NoAudio;
+
+impl AudioSink for NoAudio {
+    fn play(&mut self, _sequence: Sequence) {}
+
+    fn update(&mut self, _elapsed: Duration) {}
+}
+
+/// Keeps track of the position within a sequence, for sinks that set the frequency of a tone
+/// generator themselves
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SequencePlayer {
+    sequence: Sequence,
+    index: usize,
+    /// Time since the start of the current tone
+    time: Duration,
+}
+
+impl SequencePlayer {
+    pub const fn new() -> Self {
+        Self {
+            sequence: &[],
+            index: 0,
+            time: Duration::ZERO,
+        }
+    }
+
+    pub fn start(&mut self, sequence: Sequence) {
+        *self = Self {
+            sequence,
+            index: 0,
+            time: Duration::ZERO,
+        };
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.index < self.sequence.len()
+    }
+
+    /// Advances the playback and returns the frequency to output now, zero for silence
+    pub fn update(&mut self, elapsed: Duration) -> u16 {
+        self.time += elapsed;
+        while let Some(tone) = self.sequence.get(self.index) {
+            if self.time < tone.duration {
+                return tone.frequency_at(self.time);
+            }
+            self.time -= tone.duration;
+            self.index += 1;
+        }
+        0
+    }
+}
@@ -0,0 +1,109 @@
+//! A fixed-capacity list shared by the input and the game events, so that neither needs an allocator.
+
+/// Values that can be kept in an [`EventBuffer`]
+pub trait Event: Copy {
+    /// Fills the unused slots of a buffer, never returned from it
+    const PLACEHOLDER: Self;
+}
+
+/// A fixed-capacity list of events, e.g. the ones that happened during a single update
+#[derive(Clone, Copy, Debug)]
+pub struct EventBuffer<T, const N: usize> {
+    events: [T; N],
+    len: usize,
+}
+
+impl<T: Event, const N: usize> EventBuffer<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            events: [T::PLACEHOLDER; N],
+            len: 0,
+        }
+    }
+
+    /// Appends an event, returns `false` (dropping the event) if the buffer is full
+    pub fn push(&mut self, event: T) -> bool {
+        if self.len == N {
+            return false;
+        }
+
+        self.events[self.len] = event;
+        self.len += 1;
+        true
+    }
+
+    /// Appends all of the events that fit
+    pub fn extend(&mut self, events: &[T]) {
+        for event in events {
+            if !self.push(*event) {
+                break;
+            }
+        }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.events[..self.len]
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Removes all events from the buffer and returns them
+    pub fn take(&mut self) -> Self {
+        core::mem::take(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T: Event, const N: usize> Default for EventBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Event for u8 {
+        const PLACEHOLDER: Self = 0;
+    }
+
+    #[test]
+    fn push_drops_events_once_full() {
+        let mut buffer = EventBuffer::<u8, 2>::new();
+        assert!(buffer.push(1));
+        assert!(buffer.push(2));
+        assert!(!buffer.push(3));
+        assert_eq!(buffer.as_slice(), &[1, 2]);
+    }
+
+    #[test]
+    fn extend_keeps_the_events_that_fit() {
+        let mut buffer = EventBuffer::<u8, 3>::new();
+        buffer.push(1);
+        buffer.extend(&[2, 3, 4]);
+        assert_eq!(buffer.as_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn take_empties_the_buffer() {
+        let mut buffer = EventBuffer::<u8, 2>::new();
+        buffer.push(1);
+        let taken = buffer.take();
+        assert_eq!(taken.as_slice(), &[1]);
+        assert!(buffer.is_empty());
+
+        buffer.push(2);
+        buffer.clear();
+        assert_eq!(buffer.len(), 0);
+    }
+}
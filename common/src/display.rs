@@ -1,15 +1,7 @@
-use crate::font_monospace;
-
-/// static lookup table for strings showing the score
-const NUMBER_STR_LOOKUP_100: &[&str] = &[
-    "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16",
-    "17", "18", "19", "20", "21", "22", "23", "24", "25", "26", "27", "28", "29", "30", "31", "32",
-    "33", "34", "35", "36", "37", "38", "39", "40", "41", "42", "43", "44", "45", "46", "47", "48",
-    "49", "50", "51", "52", "53", "54", "55", "56", "57", "58", "59", "60", "61", "62", "63", "64",
-    "65", "66", "67", "68", "69", "70", "71", "72", "73", "74", "75", "76", "77", "78", "79", "80",
-    "81", "82", "83", "84", "85", "86", "87", "88", "89", "90", "91", "92", "93", "94", "95", "96",
-    "97", "98", "99", "100", ">100",
-];
+use crate::{
+    font::{Font, Glyph, Monospace},
+    number::Number,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Pixel {
@@ -17,11 +9,14 @@ pub enum Pixel {
     Off,
 }
 
+/// A surface of pixels to draw on, sized at runtime. It is dyn-compatible, so games and menus take a
+/// `&mut dyn PixelDisplay` and are compiled once no matter which display they end up on.
 pub trait PixelDisplay {
-    const ROWS: usize;
-    const COLUMNS: usize;
+    fn rows(&self) -> usize;
+
+    fn columns(&self) -> usize;
 
-    /// Sets the pixel to the desired state
+    /// Sets the pixel to the desired state, pixels outside of the display are ignored
     fn set_pixel(&mut self, row: usize, col: usize, value: Pixel);
 
     fn clear(&mut self) {
@@ -29,62 +24,59 @@ pub trait PixelDisplay {
     }
 
     fn fill(&mut self, value: Pixel) {
-        for row in 0..Self::ROWS {
-            for col in 0..Self::COLUMNS {
+        for row in 0..self.rows() {
+            for col in 0..self.columns() {
                 self.set_pixel(row, col, value);
             }
         }
     }
 
-    fn rows(&self) -> usize {
-        Self::ROWS
-    }
-    fn columns(&self) -> usize {
-        Self::COLUMNS
+    /// Draws text using the default [`Monospace`] font
+    fn draw_text(&mut self, start_row: isize, start_col: isize, text: &str) {
+        self.draw_text_with(&Monospace, start_row, start_col, text);
     }
 
-    fn draw_text(&mut self, start_row: isize, start_col: isize, text: &str) {
+    /// Draws text using the given font, with `start_row` being the top of the line
+    fn draw_text_with(&mut self, font: &dyn Font, start_row: isize, start_col: isize, text: &str) {
         let mut column = start_col;
 
         for c in text.chars() {
-            let c = c as u8;
+            let glyph = font.glyph(c);
+            self.draw_glyph(start_row, column, &glyph);
 
-            let bytes = font_monospace::get_bytes_for_char(c);
+            column = column.saturating_add(glyph.advance as isize);
+        }
+    }
 
-            // go through row by row
-            for (i, b) in bytes.iter().enumerate() {
-                // go through column by column and draw each pixel as needed
-                for j in (0..font_monospace::char_width()).rev() {
-                    if b & (1 << j) != 0 {
-                        let row = start_row + i as isize;
-                        let col = column + j as isize;
+    /// Draws a single glyph with its top left corner at the given position, clipping at the edges
+    fn draw_glyph(&mut self, start_row: isize, start_col: isize, glyph: &Glyph) {
+        // go through row by row
+        for (i, b) in glyph.rows.iter().enumerate() {
+            // go through column by column and draw each pixel as needed
+            for j in 0..glyph.width {
+                if b & (1 << (glyph.skip + j)) != 0 {
+                    let row = start_row.saturating_add(i as isize);
+                    let col = start_col.saturating_add(j as isize);
 
-                        if row >= 0
-                            && row < self.rows() as isize
-                            && col >= 0
-                            && col < self.columns() as isize
-                        {
-                            self.set_pixel(row as usize, col as usize, Pixel::On)
-                        }
+                    if row >= 0
+                        && row < self.rows() as isize
+                        && col >= 0
+                        && col < self.columns() as isize
+                    {
+                        self.set_pixel(row as usize, col as usize, Pixel::On)
                     }
                 }
             }
-
-            column += font_monospace::char_width() as isize;
         }
     }
 
-    /// Draws a number onto the screen using a look-up table.
-    /// Supports numbers up to and including 100, prints ">100" otherwise.
+    /// Draws any unsigned number using the default font, see [`Number`] for other formats
     fn draw_number(&mut self, start_row: isize, start_col: isize, number: usize) {
-        if number >= NUMBER_STR_LOOKUP_100.len() - 1 {
-            self.draw_text(
-                start_row,
-                start_col,
-                NUMBER_STR_LOOKUP_100[NUMBER_STR_LOOKUP_100.len() - 1],
-            );
-        } else {
-            self.draw_text(start_row, start_col, NUMBER_STR_LOOKUP_100[number]);
-        }
+        self.draw_text(start_row, start_col, Number::unsigned(number).as_str());
+    }
+
+    /// Draws any signed number using the default font
+    fn draw_signed_number(&mut self, start_row: isize, start_col: isize, number: isize) {
+        self.draw_text(start_row, start_col, Number::signed(number).as_str());
     }
 }
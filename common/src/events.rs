@@ -0,0 +1,41 @@
+//! Notifications from the games to the outside world, e.g. for logging, statistics or sound.
+//!
+//! A game collects the events of a single update in a [`GameEventBuffer`] and exposes them through
+//! [`Game::events`](crate::Game::events) until its next update.
+
+use crate::buffer::{Event, EventBuffer};
+
+/// The sound effects a game can ask for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cue {
+    /// A round starts
+    Start,
+    /// Something was eaten or collected
+    Eat,
+    /// One or more lines were cleared
+    Clear,
+    /// The round is over
+    Die,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// The score of the current round changed to the given value
+    ScoreChanged(usize),
+    /// The given number of lines were cleared at once
+    LineCleared(u8),
+    /// The round is over
+    Died,
+    /// A sound effect should be played
+    SoundCue(Cue),
+}
+
+/// The maximum number of events kept for a single update
+pub const FRAME_GAME_EVENTS: usize = 16;
+
+impl Event for GameEvent {
+    const PLACEHOLDER: Self = GameEvent::Died;
+}
+
+/// A fixed-capacity list of the events emitted during an update
+pub type GameEventBuffer<const N: usize = FRAME_GAME_EVENTS> = EventBuffer<GameEvent, N>;
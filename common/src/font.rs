@@ -0,0 +1,153 @@
+use crate::{font_large_digits, font_monospace, font_tiny};
+
+/// A single glyph bitmap as returned by a [`Font`]
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph {
+    /// One byte per row, the least significant bit is the leftmost column
+    pub rows: &'static [u8],
+    /// Number of (empty) columns to skip on the left side of the bitmap
+    pub skip: u8,
+    /// Number of columns of the bitmap to draw, starting after `skip`
+    pub width: u8,
+    /// Number of columns to move right after drawing this glyph
+    pub advance: u8,
+}
+
+/// A bitmap font that can be used for drawing text on a [`crate::display::PixelDisplay`]
+pub trait Font {
+    /// The height of a line of text in pixels
+    fn height(&self) -> usize;
+
+    /// The row (counted from the top of a line) that the bottom of the capital letters sits on top of
+    fn baseline(&self) -> usize;
+
+    /// Returns the glyph for a character. Fonts return a fallback glyph for characters they do not cover.
+    fn glyph(&self, character: char) -> Glyph;
+}
+
+/// The original 5x9 monospace font, covers printable ASCII and some extended Latin and symbols
+pub struct Monospace;
+
+impl Font for Monospace {
+    fn height(&self) -> usize {
+        9
+    }
+
+    fn baseline(&self) -> usize {
+        7
+    }
+
+    fn glyph(&self, character: char) -> Glyph {
+        Glyph {
+            rows: font_monospace::get_bytes_for_char(character),
+            skip: 0,
+            width: font_monospace::char_width() as u8,
+            advance: font_monospace::char_width() as u8,
+        }
+    }
+}
+
+/// Variable-width version of [`Monospace`], where each glyph is trimmed to its used columns and followed
+/// by a single column of spacing
+pub struct Proportional;
+
+impl Proportional {
+    const SPACE_ADVANCE: u8 = 3;
+}
+
+impl Font for Proportional {
+    fn height(&self) -> usize {
+        Monospace.height()
+    }
+
+    fn baseline(&self) -> usize {
+        Monospace.baseline()
+    }
+
+    fn glyph(&self, character: char) -> Glyph {
+        let rows = font_monospace::get_bytes_for_char(character);
+        let used_columns = rows.iter().fold(0, |acc, row| acc | row);
+
+        if used_columns == 0 {
+            return Glyph {
+                rows,
+                skip: 0,
+                width: 0,
+                advance: Self::SPACE_ADVANCE,
+            };
+        }
+
+        let first = used_columns.trailing_zeros() as u8;
+        let last = 7 - used_columns.leading_zeros() as u8;
+        let width = last - first + 1;
+
+        Glyph {
+            rows,
+            skip: first,
+            width,
+            advance: width + 1,
+        }
+    }
+}
+
+/// Compact 3x5 font with digits, upper case letters and some punctuation, useful for scores
+pub struct Tiny;
+
+impl Font for Tiny {
+    fn height(&self) -> usize {
+        font_tiny::char_height()
+    }
+
+    fn baseline(&self) -> usize {
+        font_tiny::char_height()
+    }
+
+    fn glyph(&self, character: char) -> Glyph {
+        Glyph {
+            rows: font_tiny::get_bytes_for_char(character),
+            skip: 0,
+            width: font_tiny::char_width() as u8,
+            advance: font_tiny::char_width() as u8 + 1,
+        }
+    }
+}
+
+/// Large 6x12 digits for clocks and big numbers, only covers `0-9`, `:`, `-` and space
+pub struct LargeDigits;
+
+impl Font for LargeDigits {
+    fn height(&self) -> usize {
+        font_large_digits::char_height()
+    }
+
+    fn baseline(&self) -> usize {
+        font_large_digits::char_height()
+    }
+
+    fn glyph(&self, character: char) -> Glyph {
+        let (width, rows) = font_large_digits::get_glyph(character);
+
+        Glyph {
+            rows,
+            skip: 0,
+            width: width as u8,
+            advance: width as u8 + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_characters_use_the_fallback_glyph() {
+        let glyph = Monospace.glyph('€');
+        assert_eq!(glyph.rows, font_monospace::FALLBACK);
+        assert_eq!(glyph.advance, 5);
+
+        let glyph = Proportional.glyph('€');
+        assert_eq!(glyph.rows, font_monospace::FALLBACK);
+        assert_eq!((glyph.skip, glyph.width, glyph.advance), (0, 5, 6));
+    }
+}
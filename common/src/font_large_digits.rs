@@ -0,0 +1,177 @@
+//
+// large_digits
+// Font Size: 6x12px, digits and the separators needed for clocks and scores
+//
+// Each glyph is stored as one byte per row, with the least significant bit being the leftmost column,
+// together with the width of the glyph in columns.
+//
+
+pub const fn char_height() -> usize {
+    12
+}
+
+/// Returns the width and rows of the glyph for a character, or the [`FALLBACK`] glyph for characters
+/// not covered by the font
+pub fn get_glyph(character: char) -> (usize, &'static [u8]) {
+    match LARGE_DIGITS.binary_search_by_key(&character, |(c, _, _)| *c) {
+        Ok(index) => (LARGE_DIGITS[index].1 as usize, &LARGE_DIGITS[index].2),
+        Err(_) => (6, &FALLBACK),
+    }
+}
+
+/// Hollow box drawn for all characters not covered by the font
+pub const FALLBACK: [u8; 12] = [
+    0x3F, 0x21, 0x21, 0x21, 0x21, 0x21, 0x21, 0x21, 0x21, 0x21, 0x21, 0x3F,
+];
+
+/// Sorted by character so it can be binary searched
+const LARGE_DIGITS: [(char, u8, [u8; 12]); 13] = [
+    // ' '
+    (
+        ' ',
+        4,
+        [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+    ),
+    // '-'
+    (
+        '-',
+        4,
+        [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x0F, 0x0F, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ],
+    ),
+    // '0'
+    (
+        '0',
+        6,
+        [
+            0x1E, 0x3F, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x1E,
+        ],
+    ),
+    // '1'
+    (
+        '1',
+        6,
+        [
+            0x0C, 0x0E, 0x0F, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x3F,
+        ],
+    ),
+    // '2'
+    (
+        '2',
+        6,
+        [
+            0x1E, 0x3F, 0x33, 0x30, 0x30, 0x38, 0x1C, 0x0E, 0x07, 0x03, 0x3F, 0x3F,
+        ],
+    ),
+    // '3'
+    (
+        '3',
+        6,
+        [
+            0x1E, 0x3F, 0x33, 0x30, 0x30, 0x1C, 0x1C, 0x30, 0x30, 0x33, 0x3F, 0x1E,
+        ],
+    ),
+    // '4'
+    (
+        '4',
+        6,
+        [
+            0x38, 0x3C, 0x36, 0x33, 0x33, 0x3F, 0x3F, 0x30, 0x30, 0x30, 0x30, 0x30,
+        ],
+    ),
+    // '5'
+    (
+        '5',
+        6,
+        [
+            0x3F, 0x3F, 0x03, 0x03, 0x1F, 0x3F, 0x30, 0x30, 0x30, 0x33, 0x3F, 0x1E,
+        ],
+    ),
+    // '6'
+    (
+        '6',
+        6,
+        [
+            0x1E, 0x3F, 0x33, 0x03, 0x03, 0x1F, 0x3F, 0x33, 0x33, 0x33, 0x3F, 0x1E,
+        ],
+    ),
+    // '7'
+    (
+        '7',
+        6,
+        [
+            0x3F, 0x3F, 0x30, 0x30, 0x18, 0x18, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C,
+        ],
+    ),
+    // '8'
+    (
+        '8',
+        6,
+        [
+            0x1E, 0x3F, 0x33, 0x33, 0x33, 0x1E, 0x1E, 0x33, 0x33, 0x33, 0x3F, 0x1E,
+        ],
+    ),
+    // '9'
+    (
+        '9',
+        6,
+        [
+            0x1E, 0x3F, 0x33, 0x33, 0x33, 0x3F, 0x3E, 0x30, 0x30, 0x33, 0x3F, 0x1E,
+        ],
+    ),
+    // ':'
+    (
+        ':',
+        2,
+        [
+            0x00, 0x00, 0x00, 0x03, 0x03, 0x00, 0x00, 0x03, 0x03, 0x00, 0x00, 0x00,
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_strictly_ascending() {
+        for pair in LARGE_DIGITS.windows(2) {
+            let (first, second) = (pair[0].0, pair[1].0);
+            assert!(first < second, "{first:?} before {second:?}");
+        }
+    }
+
+    #[test]
+    fn every_covered_character_is_found() {
+        for (character, width, rows) in LARGE_DIGITS.iter() {
+            assert_eq!(
+                get_glyph(*character),
+                (*width as usize, &rows[..]),
+                "{character:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn glyphs_fit_their_width() {
+        let glyphs = LARGE_DIGITS
+            .iter()
+            .map(|(_, width, rows)| (*width as usize, rows))
+            .chain([(6, &FALLBACK)]);
+        for (width, rows) in glyphs {
+            assert!(width <= 6);
+            assert_eq!(rows.len(), char_height());
+            assert!(rows.iter().all(|row| row >> width == 0), "{rows:?}");
+        }
+    }
+
+    #[test]
+    fn missing_characters_fall_back() {
+        for character in ['\0', 'A', '.', '€', char::MAX] {
+            assert_eq!(get_glyph(character), (6, &FALLBACK[..]), "{character:?}");
+        }
+    }
+}
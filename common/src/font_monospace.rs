@@ -4,6 +4,10 @@
 // Created: 24-05-2023 21:58:43
 //
 
+//
+// Extended with a table of non-ASCII glyphs (accented latin letters and a few symbols).
+//
+
 //
 // Pseudocode for retrieving data for a specific character:
 //
@@ -11,20 +15,40 @@
 // data = monospace_6pt[lut[offset]]
 //
 
-pub fn get_bytes_for_char(character: u8) -> &'static [u8] {
-    let start = LUT[(character - 0x20) as usize] as usize;
+/// Returns the glyph for a character, or the [`FALLBACK`] glyph for characters not covered by the
+/// font. Printable ASCII is looked up directly, other characters in the (sorted) extended table.
+pub fn get_bytes_for_char(character: char) -> &'static [u8] {
+    let code = character as u32;
+
+    if (0x20..0x7f).contains(&code) {
+        let start = LUT[(code - 0x20) as usize] as usize;
+        return &MONOSPACE_6PT[start..start + 9];
+    }
 
-    &MONOSPACE_6PT[start..start + 9]
+    match EXTENDED.binary_search_by_key(&character, |(c, _)| *c) {
+        Ok(index) => &EXTENDED[index].1,
+        Err(_) => &FALLBACK,
+    }
 }
 
 pub const fn char_width() -> usize {
     5
 }
 
-const MONOSPACE_6PT: [u8; 774] = [
+/// Hollow box drawn for all characters not covered by the font
+pub const FALLBACK: [u8; 9] = [0x00, 0x1F, 0x11, 0x11, 0x11, 0x11, 0x1F, 0x00, 0x00];
+
+const MONOSPACE_6PT: [u8; 855] = [
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Character 0x20 (32: ' ')
     0x00, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04, 0x00, 0x00, // Character 0x21 (33: '!')
+    0x00, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Character 0x22 (34: '"')
+    0x00, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x00, 0x00, 0x00, // Character 0x23 (35: '#')
+    0x04, 0x1E, 0x05, 0x0E, 0x14, 0x0F, 0x04, 0x00, 0x00, // Character 0x24 (36: '$')
+    0x00, 0x13, 0x0B, 0x04, 0x1A, 0x19, 0x00, 0x00, 0x00, // Character 0x25 (37: '%')
+    0x00, 0x06, 0x09, 0x06, 0x15, 0x09, 0x16, 0x00, 0x00, // Character 0x26 (38: '&')
     0x00, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Character 0x27 (39: ''')
+    0x00, 0x08, 0x04, 0x04, 0x04, 0x04, 0x08, 0x00, 0x00, // Character 0x28 (40: '(')
+    0x00, 0x02, 0x04, 0x04, 0x04, 0x04, 0x02, 0x00, 0x00, // Character 0x29 (41: ')')
     0x00, 0x15, 0x0E, 0x0E, 0x15, 0x00, 0x00, 0x00, 0x00, // Character 0x2a (42: '*')
     0x00, 0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, 0x00, // Character 0x2b (43: '+')
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x04, 0x00, // Character 0x2c (44: ',')
@@ -74,7 +98,9 @@ const MONOSPACE_6PT: [u8; 774] = [
     0x00, 0x12, 0x0C, 0x0C, 0x0C, 0x0C, 0x12, 0x00, 0x00, // Character 0x58 (88: 'X')
     0x00, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04, 0x00, 0x00, // Character 0x59 (89: 'Y')
     0x00, 0x1E, 0x08, 0x08, 0x04, 0x04, 0x1E, 0x00, 0x00, // Character 0x5a (90: 'Z')
+    0x00, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0C, 0x00, 0x00, // Character 0x5b (91: '[')
     0x00, 0x01, 0x02, 0x02, 0x06, 0x04, 0x04, 0x08, 0x00, // Character 0x5c (92: '\')
+    0x00, 0x06, 0x04, 0x04, 0x04, 0x04, 0x06, 0x00, 0x00, // Character 0x5d (93: ']')
     0x00, 0x06, 0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Character 0x5e (94: '^')
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, // Character 0x5f (95: '_')
     0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Character 0x60 (96: '`')
@@ -111,90 +137,163 @@ const MONOSPACE_6PT: [u8; 774] = [
 ];
 
 const LUT: [u16; 95] = [
-    0, // Character 0x20 (32: ' ')
-    9, // Character 0x21 (33: '!')
-    0, 0, 0, 0, 0, 18, // Character 0x27 (39: ''')
-    0, 0, 27,  // Character 0x2a (42: '*')
-    36,  // Character 0x2b (43: '+')
-    45,  // Character 0x2c (44: ',')
-    54,  // Character 0x2d (45: '-')
-    63,  // Character 0x2e (46: '.')
-    72,  // Character 0x2f (47: '/')
-    81,  // Character 0x30 (48: '0')
-    90,  // Character 0x31 (49: '1')
-    99,  // Character 0x32 (50: '2')
-    108, // Character 0x33 (51: '3')
-    117, // Character 0x34 (52: '4')
-    126, // Character 0x35 (53: '5')
-    135, // Character 0x36 (54: '6')
-    144, // Character 0x37 (55: '7')
-    153, // Character 0x38 (56: '8')
-    162, // Character 0x39 (57: '9')
-    171, // Character 0x3a (58: ':')
-    180, // Character 0x3b (59: ';')
-    189, // Character 0x3c (60: '<')
-    198, // Character 0x3d (61: '=')
-    207, // Character 0x3e (62: '>')
-    216, // Character 0x3f (63: '?')
-    225, // Character 0x40 (64: '@')
-    234, // Character 0x41 (65: 'A')
-    243, // Character 0x42 (66: 'B')
-    252, // Character 0x43 (67: 'C')
-    261, // Character 0x44 (68: 'D')
-    270, // Character 0x45 (69: 'E')
-    279, // Character 0x46 (70: 'F')
-    288, // Character 0x47 (71: 'G')
-    297, // Character 0x48 (72: 'H')
-    306, // Character 0x49 (73: 'I')
-    315, // Character 0x4a (74: 'J')
-    324, // Character 0x4b (75: 'K')
-    333, // Character 0x4c (76: 'L')
-    342, // Character 0x4d (77: 'M')
-    351, // Character 0x4e (78: 'N')
-    360, // Character 0x4f (79: 'O')
-    369, // Character 0x50 (80: 'P')
-    378, // Character 0x51 (81: 'Q')
-    387, // Character 0x52 (82: 'R')
-    396, // Character 0x53 (83: 'S')
-    405, // Character 0x54 (84: 'T')
-    414, // Character 0x55 (85: 'U')
-    423, // Character 0x56 (86: 'V')
-    432, // Character 0x57 (87: 'W')
-    441, // Character 0x58 (88: 'X')
-    450, // Character 0x59 (89: 'Y')
-    459, // Character 0x5a (90: 'Z')
-    0, 468, // Character 0x5c (92: '\')
-    0, 477, // Character 0x5e (94: '^')
-    486, // Character 0x5f (95: '_')
-    495, // Character 0x60 (96: '`')
-    504, // Character 0x61 (97: 'a')
-    513, // Character 0x62 (98: 'b')
-    522, // Character 0x63 (99: 'c')
-    531, // Character 0x64 (100: 'd')
-    540, // Character 0x65 (101: 'e')
-    549, // Character 0x66 (102: 'f')
-    558, // Character 0x67 (103: 'g')
-    567, // Character 0x68 (104: 'h')
-    576, // Character 0x69 (105: 'i')
-    585, // Character 0x6a (106: 'j')
-    594, // Character 0x6b (107: 'k')
-    603, // Character 0x6c (108: 'l')
-    612, // Character 0x6d (109: 'm')
-    621, // Character 0x6e (110: 'n')
-    630, // Character 0x6f (111: 'o')
-    639, // Character 0x70 (112: 'p')
-    648, // Character 0x71 (113: 'q')
-    657, // Character 0x72 (114: 'r')
-    666, // Character 0x73 (115: 's')
-    675, // Character 0x74 (116: 't')
-    684, // Character 0x75 (117: 'u')
-    693, // Character 0x76 (118: 'v')
-    702, // Character 0x77 (119: 'w')
-    711, // Character 0x78 (120: 'x')
-    720, // Character 0x79 (121: 'y')
-    729, // Character 0x7a (122: 'z')
-    738, // Character 0x7b (123: '{')
-    747, // Character 0x7c (124: '|')
-    756, // Character 0x7d (125: '}')
-    765, // Character 0x7e (126: '~')
+    0,   // Character 0x20 (32: ' ')
+    9,   // Character 0x21 (33: '!')
+    18,  // Character 0x22 (34: '"')
+    27,  // Character 0x23 (35: '#')
+    36,  // Character 0x24 (36: '$')
+    45,  // Character 0x25 (37: '%')
+    54,  // Character 0x26 (38: '&')
+    63,  // Character 0x27 (39: ''')
+    72,  // Character 0x28 (40: '(')
+    81,  // Character 0x29 (41: ')')
+    90,  // Character 0x2a (42: '*')
+    99,  // Character 0x2b (43: '+')
+    108, // Character 0x2c (44: ',')
+    117, // Character 0x2d (45: '-')
+    126, // Character 0x2e (46: '.')
+    135, // Character 0x2f (47: '/')
+    144, // Character 0x30 (48: '0')
+    153, // Character 0x31 (49: '1')
+    162, // Character 0x32 (50: '2')
+    171, // Character 0x33 (51: '3')
+    180, // Character 0x34 (52: '4')
+    189, // Character 0x35 (53: '5')
+    198, // Character 0x36 (54: '6')
+    207, // Character 0x37 (55: '7')
+    216, // Character 0x38 (56: '8')
+    225, // Character 0x39 (57: '9')
+    234, // Character 0x3a (58: ':')
+    243, // Character 0x3b (59: ';')
+    252, // Character 0x3c (60: '<')
+    261, // Character 0x3d (61: '=')
+    270, // Character 0x3e (62: '>')
+    279, // Character 0x3f (63: '?')
+    288, // Character 0x40 (64: '@')
+    297, // Character 0x41 (65: 'A')
+    306, // Character 0x42 (66: 'B')
+    315, // Character 0x43 (67: 'C')
+    324, // Character 0x44 (68: 'D')
+    333, // Character 0x45 (69: 'E')
+    342, // Character 0x46 (70: 'F')
+    351, // Character 0x47 (71: 'G')
+    360, // Character 0x48 (72: 'H')
+    369, // Character 0x49 (73: 'I')
+    378, // Character 0x4a (74: 'J')
+    387, // Character 0x4b (75: 'K')
+    396, // Character 0x4c (76: 'L')
+    405, // Character 0x4d (77: 'M')
+    414, // Character 0x4e (78: 'N')
+    423, // Character 0x4f (79: 'O')
+    432, // Character 0x50 (80: 'P')
+    441, // Character 0x51 (81: 'Q')
+    450, // Character 0x52 (82: 'R')
+    459, // Character 0x53 (83: 'S')
+    468, // Character 0x54 (84: 'T')
+    477, // Character 0x55 (85: 'U')
+    486, // Character 0x56 (86: 'V')
+    495, // Character 0x57 (87: 'W')
+    504, // Character 0x58 (88: 'X')
+    513, // Character 0x59 (89: 'Y')
+    522, // Character 0x5a (90: 'Z')
+    531, // Character 0x5b (91: '[')
+    540, // Character 0x5c (92: '\')
+    549, // Character 0x5d (93: ']')
+    558, // Character 0x5e (94: '^')
+    567, // Character 0x5f (95: '_')
+    576, // Character 0x60 (96: '`')
+    585, // Character 0x61 (97: 'a')
+    594, // Character 0x62 (98: 'b')
+    603, // Character 0x63 (99: 'c')
+    612, // Character 0x64 (100: 'd')
+    621, // Character 0x65 (101: 'e')
+    630, // Character 0x66 (102: 'f')
+    639, // Character 0x67 (103: 'g')
+    648, // Character 0x68 (104: 'h')
+    657, // Character 0x69 (105: 'i')
+    666, // Character 0x6a (106: 'j')
+    675, // Character 0x6b (107: 'k')
+    684, // Character 0x6c (108: 'l')
+    693, // Character 0x6d (109: 'm')
+    702, // Character 0x6e (110: 'n')
+    711, // Character 0x6f (111: 'o')
+    720, // Character 0x70 (112: 'p')
+    729, // Character 0x71 (113: 'q')
+    738, // Character 0x72 (114: 'r')
+    747, // Character 0x73 (115: 's')
+    756, // Character 0x74 (116: 't')
+    765, // Character 0x75 (117: 'u')
+    774, // Character 0x76 (118: 'v')
+    783, // Character 0x77 (119: 'w')
+    792, // Character 0x78 (120: 'x')
+    801, // Character 0x79 (121: 'y')
+    810, // Character 0x7a (122: 'z')
+    819, // Character 0x7b (123: '{')
+    828, // Character 0x7c (124: '|')
+    837, // Character 0x7d (125: '}')
+    846, // Character 0x7e (126: '~')
+];
+
+/// Glyphs outside of ASCII, sorted by character so they can be binary searched
+const EXTENDED: [(char, [u8; 9]); 20] = [
+    ('°', [0x00, 0x04, 0x0A, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]), // U+00B0 DEGREE SIGN
+    ('Ä', [0x12, 0x00, 0x0C, 0x12, 0x1E, 0x12, 0x12, 0x00, 0x00]), // U+00C4 LATIN CAPITAL LETTER A WITH DIAERESIS
+    ('Å', [0x0C, 0x12, 0x0C, 0x12, 0x1E, 0x12, 0x12, 0x00, 0x00]), // U+00C5 LATIN CAPITAL LETTER A WITH RING ABOVE
+    ('É', [0x10, 0x08, 0x1E, 0x02, 0x0E, 0x02, 0x1E, 0x00, 0x00]), // U+00C9 LATIN CAPITAL LETTER E WITH ACUTE
+    ('Ö', [0x12, 0x00, 0x0C, 0x12, 0x12, 0x12, 0x0C, 0x00, 0x00]), // U+00D6 LATIN CAPITAL LETTER O WITH DIAERESIS
+    ('Ü', [0x12, 0x00, 0x12, 0x12, 0x12, 0x12, 0x0C, 0x00, 0x00]), // U+00DC LATIN CAPITAL LETTER U WITH DIAERESIS
+    ('ß', [0x0C, 0x12, 0x12, 0x0A, 0x12, 0x12, 0x0A, 0x00, 0x00]), // U+00DF LATIN SMALL LETTER SHARP S
+    ('ä', [0x00, 0x12, 0x00, 0x1E, 0x1E, 0x12, 0x1E, 0x00, 0x00]), // U+00E4 LATIN SMALL LETTER A WITH DIAERESIS
+    ('å', [0x04, 0x0A, 0x04, 0x1E, 0x1E, 0x12, 0x1E, 0x00, 0x00]), // U+00E5 LATIN SMALL LETTER A WITH RING ABOVE
+    ('ç', [0x00, 0x00, 0x00, 0x0C, 0x02, 0x02, 0x0C, 0x04, 0x06]), // U+00E7 LATIN SMALL LETTER C WITH CEDILLA
+    ('è', [0x00, 0x04, 0x08, 0x1C, 0x1E, 0x02, 0x1C, 0x00, 0x00]), // U+00E8 LATIN SMALL LETTER E WITH GRAVE
+    ('é', [0x00, 0x08, 0x04, 0x1C, 0x1E, 0x02, 0x1C, 0x00, 0x00]), // U+00E9 LATIN SMALL LETTER E WITH ACUTE
+    ('ñ', [0x00, 0x14, 0x0A, 0x1E, 0x12, 0x12, 0x12, 0x00, 0x00]), // U+00F1 LATIN SMALL LETTER N WITH TILDE
+    ('ö', [0x00, 0x12, 0x00, 0x0C, 0x12, 0x12, 0x0C, 0x00, 0x00]), // U+00F6 LATIN SMALL LETTER O WITH DIAERESIS
+    ('ü', [0x00, 0x12, 0x00, 0x12, 0x12, 0x12, 0x1E, 0x00, 0x00]), // U+00FC LATIN SMALL LETTER U WITH DIAERESIS
+    ('←', [0x00, 0x00, 0x04, 0x02, 0x1F, 0x02, 0x04, 0x00, 0x00]), // U+2190 LEFTWARDS ARROW
+    ('↑', [0x00, 0x04, 0x0E, 0x15, 0x04, 0x04, 0x04, 0x00, 0x00]), // U+2191 UPWARDS ARROW
+    ('→', [0x00, 0x00, 0x04, 0x08, 0x1F, 0x08, 0x04, 0x00, 0x00]), // U+2192 RIGHTWARDS ARROW
+    ('↓', [0x00, 0x04, 0x04, 0x04, 0x15, 0x0E, 0x04, 0x00, 0x00]), // U+2193 DOWNWARDS ARROW
+    ('♥', [0x00, 0x00, 0x1B, 0x1F, 0x1F, 0x0E, 0x04, 0x00, 0x00]), // U+2665 BLACK HEART SUIT
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_table_is_strictly_ascending() {
+        for pair in EXTENDED.windows(2) {
+            let (first, second) = (pair[0].0, pair[1].0);
+            assert!(first < second, "{first:?} before {second:?}");
+        }
+    }
+
+    #[test]
+    fn every_covered_character_is_found() {
+        for code in 0x20..0x7f {
+            let character = char::from_u32(code).unwrap();
+            assert_eq!(get_bytes_for_char(character).len(), 9, "{character:?}");
+        }
+        for (character, rows) in EXTENDED.iter() {
+            assert_eq!(get_bytes_for_char(*character), rows, "{character:?}");
+        }
+    }
+
+    #[test]
+    fn glyphs_fit_the_width() {
+        let ascii = (0x20..0x7f).map(|code| get_bytes_for_char(char::from_u32(code).unwrap()));
+        let extended = EXTENDED.iter().map(|(_, rows)| &rows[..]);
+        for rows in ascii.chain(extended).chain([&FALLBACK[..]]) {
+            assert!(rows.iter().all(|row| row >> char_width() == 0), "{rows:?}");
+        }
+    }
+
+    #[test]
+    fn missing_characters_fall_back() {
+        for character in ['\0', '\n', '\u{7f}', '€', '😀', char::MAX] {
+            assert_eq!(get_bytes_for_char(character), FALLBACK, "{character:?}");
+        }
+    }
+}
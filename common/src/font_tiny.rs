@@ -0,0 +1,121 @@
+//
+// tiny_3x5
+// Font Size: 3x5px, upper case letters, digits and some punctuation
+//
+// Each glyph is stored as one byte per row, with the least significant bit being the leftmost column.
+// Lower case letters are drawn using their upper case glyph.
+//
+
+pub const fn char_width() -> usize {
+    3
+}
+
+pub const fn char_height() -> usize {
+    5
+}
+
+/// Returns the glyph for a character, or the [`FALLBACK`] glyph for characters not covered by the font
+pub fn get_bytes_for_char(character: char) -> &'static [u8] {
+    let character = character.to_ascii_uppercase();
+
+    match TINY_3X5.binary_search_by_key(&character, |(c, _)| *c) {
+        Ok(index) => &TINY_3X5[index].1,
+        Err(_) => &FALLBACK,
+    }
+}
+
+/// Hollow box drawn for all characters not covered by the font
+pub const FALLBACK: [u8; 5] = [0x7, 0x5, 0x5, 0x5, 0x7];
+
+/// Sorted by character so it can be binary searched
+const TINY_3X5: [(char, [u8; 5]); 52] = [
+    (' ', [0x0, 0x0, 0x0, 0x0, 0x0]),  // ... ... ... ... ...
+    ('!', [0x2, 0x2, 0x2, 0x0, 0x2]),  // .#. .#. .#. ... .#.
+    ('\'', [0x2, 0x2, 0x0, 0x0, 0x0]), // .#. .#. ... ... ...
+    ('(', [0x4, 0x2, 0x2, 0x2, 0x4]),  // ..# .#. .#. .#. ..#
+    (')', [0x1, 0x2, 0x2, 0x2, 0x1]),  // #.. .#. .#. .#. #..
+    ('*', [0x0, 0x5, 0x2, 0x5, 0x0]),  // ... #.# .#. #.# ...
+    ('+', [0x0, 0x2, 0x7, 0x2, 0x0]),  // ... .#. ### .#. ...
+    (',', [0x0, 0x0, 0x0, 0x2, 0x1]),  // ... ... ... .#. #..
+    ('-', [0x0, 0x0, 0x7, 0x0, 0x0]),  // ... ... ### ... ...
+    ('.', [0x0, 0x0, 0x0, 0x0, 0x2]),  // ... ... ... ... .#.
+    ('/', [0x4, 0x4, 0x2, 0x1, 0x1]),  // ..# ..# .#. #.. #..
+    ('0', [0x7, 0x5, 0x5, 0x5, 0x7]),  // ### #.# #.# #.# ###
+    ('1', [0x2, 0x3, 0x2, 0x2, 0x7]),  // .#. ##. .#. .#. ###
+    ('2', [0x3, 0x4, 0x2, 0x1, 0x7]),  // ##. ..# .#. #.. ###
+    ('3', [0x3, 0x4, 0x2, 0x4, 0x3]),  // ##. ..# .#. ..# ##.
+    ('4', [0x5, 0x5, 0x7, 0x4, 0x4]),  // #.# #.# ### ..# ..#
+    ('5', [0x7, 0x1, 0x3, 0x4, 0x3]),  // ### #.. ##. ..# ##.
+    ('6', [0x6, 0x1, 0x7, 0x5, 0x7]),  // .## #.. ### #.# ###
+    ('7', [0x7, 0x4, 0x2, 0x2, 0x2]),  // ### ..# .#. .#. .#.
+    ('8', [0x7, 0x5, 0x7, 0x5, 0x7]),  // ### #.# ### #.# ###
+    ('9', [0x7, 0x5, 0x7, 0x4, 0x3]),  // ### #.# ### ..# ##.
+    (':', [0x0, 0x2, 0x0, 0x2, 0x0]),  // ... .#. ... .#. ...
+    ('<', [0x4, 0x2, 0x1, 0x2, 0x4]),  // ..# .#. #.. .#. ..#
+    ('=', [0x0, 0x7, 0x0, 0x7, 0x0]),  // ... ### ... ### ...
+    ('>', [0x1, 0x2, 0x4, 0x2, 0x1]),  // #.. .#. ..# .#. #..
+    ('?', [0x3, 0x4, 0x2, 0x0, 0x2]),  // ##. ..# .#. ... .#.
+    ('A', [0x2, 0x5, 0x7, 0x5, 0x5]),  // .#. #.# ### #.# #.#
+    ('B', [0x3, 0x5, 0x3, 0x5, 0x3]),  // ##. #.# ##. #.# ##.
+    ('C', [0x6, 0x1, 0x1, 0x1, 0x6]),  // .## #.. #.. #.. .##
+    ('D', [0x3, 0x5, 0x5, 0x5, 0x3]),  // ##. #.# #.# #.# ##.
+    ('E', [0x7, 0x1, 0x3, 0x1, 0x7]),  // ### #.. ##. #.. ###
+    ('F', [0x7, 0x1, 0x3, 0x1, 0x1]),  // ### #.. ##. #.. #..
+    ('G', [0x6, 0x1, 0x5, 0x5, 0x6]),  // .## #.. #.# #.# .##
+    ('H', [0x5, 0x5, 0x7, 0x5, 0x5]),  // #.# #.# ### #.# #.#
+    ('I', [0x7, 0x2, 0x2, 0x2, 0x7]),  // ### .#. .#. .#. ###
+    ('J', [0x4, 0x4, 0x4, 0x5, 0x2]),  // ..# ..# ..# #.# .#.
+    ('K', [0x5, 0x5, 0x3, 0x5, 0x5]),  // #.# #.# ##. #.# #.#
+    ('L', [0x1, 0x1, 0x1, 0x1, 0x7]),  // #.. #.. #.. #.. ###
+    ('M', [0x5, 0x7, 0x7, 0x5, 0x5]),  // #.# ### ### #.# #.#
+    ('N', [0x3, 0x5, 0x5, 0x5, 0x5]),  // ##. #.# #.# #.# #.#
+    ('O', [0x2, 0x5, 0x5, 0x5, 0x2]),  // .#. #.# #.# #.# .#.
+    ('P', [0x3, 0x5, 0x3, 0x1, 0x1]),  // ##. #.# ##. #.. #..
+    ('Q', [0x2, 0x5, 0x5, 0x3, 0x6]),  // .#. #.# #.# ##. .##
+    ('R', [0x3, 0x5, 0x3, 0x5, 0x5]),  // ##. #.# ##. #.# #.#
+    ('S', [0x6, 0x1, 0x2, 0x4, 0x3]),  // .## #.. .#. ..# ##.
+    ('T', [0x7, 0x2, 0x2, 0x2, 0x2]),  // ### .#. .#. .#. .#.
+    ('U', [0x5, 0x5, 0x5, 0x5, 0x7]),  // #.# #.# #.# #.# ###
+    ('V', [0x5, 0x5, 0x5, 0x5, 0x2]),  // #.# #.# #.# #.# .#.
+    ('W', [0x5, 0x5, 0x7, 0x7, 0x5]),  // #.# #.# ### ### #.#
+    ('X', [0x5, 0x5, 0x2, 0x5, 0x5]),  // #.# #.# .#. #.# #.#
+    ('Y', [0x5, 0x5, 0x2, 0x2, 0x2]),  // #.# #.# .#. .#. .#.
+    ('Z', [0x7, 0x4, 0x2, 0x1, 0x7]),  // ### ..# .#. #.. ###
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_strictly_ascending() {
+        for pair in TINY_3X5.windows(2) {
+            let (first, second) = (pair[0].0, pair[1].0);
+            assert!(first < second, "{first:?} before {second:?}");
+        }
+    }
+
+    #[test]
+    fn every_covered_character_is_found() {
+        for (character, rows) in TINY_3X5.iter() {
+            assert_eq!(get_bytes_for_char(*character), rows, "{character:?}");
+        }
+        assert_eq!(get_bytes_for_char('a'), get_bytes_for_char('A'));
+    }
+
+    #[test]
+    fn glyphs_fit_the_size() {
+        let glyphs = TINY_3X5.iter().map(|(_, rows)| rows).chain([&FALLBACK]);
+        for rows in glyphs {
+            assert_eq!(rows.len(), char_height());
+            assert!(rows.iter().all(|row| row >> char_width() == 0), "{rows:?}");
+        }
+    }
+
+    #[test]
+    fn missing_characters_fall_back() {
+        for character in ['\0', '~', 'ä', '€', char::MAX] {
+            assert_eq!(get_bytes_for_char(character), FALLBACK, "{character:?}");
+        }
+    }
+}
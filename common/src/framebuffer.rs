@@ -0,0 +1,189 @@
+use crate::display::{Pixel, PixelDisplay};
+
+/// A bit-packed frame store with one `u64` per row, where bit `n` holds column `n`.
+/// Supports displays up to 64 columns wide, which covers all panels we drive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Framebuffer<const ROWS: usize, const COLS: usize> {
+    rows: [u64; ROWS],
+}
+
+impl<const ROWS: usize, const COLS: usize> Framebuffer<ROWS, COLS> {
+    pub const fn new() -> Self {
+        const { assert!(COLS <= 64, "Framebuffer supports at most 64 columns") };
+
+        Self { rows: [0; ROWS] }
+    }
+
+    /// Reads back the state of a pixel, returns [`Pixel::Off`] for coordinates outside the frame
+    pub fn get_pixel(&self, row: usize, col: usize) -> Pixel {
+        if row < ROWS && col < COLS && self.rows[row] & (1 << col) != 0 {
+            Pixel::On
+        } else {
+            Pixel::Off
+        }
+    }
+
+    /// Copies the contents of another frame into this one
+    pub fn copy_from(&mut self, other: &Self) {
+        self.rows = other.rows;
+    }
+
+    /// Iterates over all pixels (row, column, new value) that differ between this frame and `previous`
+    pub fn changes<'a>(
+        &'a self,
+        previous: &'a Self,
+    ) -> impl Iterator<Item = (usize, usize, Pixel)> + 'a {
+        self.rows
+            .iter()
+            .zip(previous.rows.iter())
+            .enumerate()
+            .flat_map(|(row, (current, previous))| {
+                let current = *current;
+                let mut diff = current ^ previous;
+
+                core::iter::from_fn(move || {
+                    if diff == 0 {
+                        return None;
+                    }
+
+                    // take the lowest changed column and clear it from the remaining set
+                    let col = diff.trailing_zeros() as usize;
+                    diff &= diff - 1;
+
+                    let value = if current & (1 << col) != 0 {
+                        Pixel::On
+                    } else {
+                        Pixel::Off
+                    };
+                    Some((row, col, value))
+                })
+            })
+    }
+
+    /// Iterates over all pixels in the frame, regardless of whether they changed or not
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize, Pixel)> + '_ {
+        (0..ROWS)
+            .flat_map(move |row| (0..COLS).map(move |col| (row, col, self.get_pixel(row, col))))
+    }
+
+    fn row_mask() -> u64 {
+        if COLS == 64 {
+            u64::MAX
+        } else {
+            (1 << COLS) - 1
+        }
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> Default for Framebuffer<ROWS, COLS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const ROWS: usize, const COLS: usize> PixelDisplay for Framebuffer<ROWS, COLS> {
+    fn rows(&self) -> usize {
+        ROWS
+    }
+
+    fn columns(&self) -> usize {
+        COLS
+    }
+
+    fn set_pixel(&mut self, row: usize, col: usize, value: Pixel) {
+        if row >= ROWS || col >= COLS {
+            return;
+        }
+
+        match value {
+            Pixel::On => self.rows[row] |= 1 << col,
+            Pixel::Off => self.rows[row] &= !(1 << col),
+        }
+    }
+
+    fn fill(&mut self, value: Pixel) {
+        let row_value = match value {
+            Pixel::On => Self::row_mask(),
+            Pixel::Off => 0,
+        };
+        self.rows = [row_value; ROWS];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn changes_yields_the_changed_pixels_with_their_new_values() {
+        let mut previous = Framebuffer::<4, 10>::new();
+        previous.set_pixel(0, 0, Pixel::On);
+        previous.set_pixel(2, 5, Pixel::On);
+
+        let mut current = previous;
+        current.set_pixel(0, 0, Pixel::Off);
+        current.set_pixel(1, 9, Pixel::On);
+        current.set_pixel(3, 3, Pixel::On);
+
+        let changes: Vec<_> = current.changes(&previous).collect();
+        assert_eq!(
+            changes,
+            [(0, 0, Pixel::Off), (1, 9, Pixel::On), (3, 3, Pixel::On)]
+        );
+    }
+
+    #[test]
+    fn fill_only_sets_the_columns_of_the_frame() {
+        fn check<const ROWS: usize, const COLS: usize>() {
+            let mut filled = Framebuffer::<ROWS, COLS>::new();
+            filled.fill(Pixel::On);
+
+            let mut expected = Framebuffer::<ROWS, COLS>::new();
+            for row in 0..ROWS {
+                for col in 0..COLS {
+                    expected.set_pixel(row, col, Pixel::On);
+                }
+            }
+
+            assert_eq!(filled, expected);
+            assert_eq!(filled.changes(&Framebuffer::new()).count(), ROWS * COLS);
+
+            filled.fill(Pixel::Off);
+            assert_eq!(filled, Framebuffer::new());
+        }
+
+        check::<3, 16>();
+        check::<2, 63>();
+        check::<2, 64>();
+    }
+
+    #[test]
+    fn copy_from_leaves_no_changes() {
+        let mut active = Framebuffer::<3, 8>::new();
+        active.set_pixel(1, 2, Pixel::On);
+        active.set_pixel(2, 7, Pixel::On);
+
+        let mut shadow = Framebuffer::new();
+        assert_eq!(active.changes(&shadow).count(), 2);
+
+        shadow.copy_from(&active);
+        assert_eq!(active.changes(&shadow).count(), 0);
+    }
+
+    #[test]
+    fn pixels_outside_the_frame_are_ignored() {
+        let mut frame = Framebuffer::<2, 4>::new();
+        frame.set_pixel(2, 0, Pixel::On);
+        frame.set_pixel(0, 4, Pixel::On);
+        frame.set_pixel(usize::MAX, usize::MAX, Pixel::On);
+
+        assert_eq!(frame, Framebuffer::new());
+        assert_eq!(frame.get_pixel(2, 0), Pixel::Off);
+        assert_eq!(frame.get_pixel(0, 4), Pixel::Off);
+        assert_eq!(frame.get_pixel(0, 100), Pixel::Off);
+    }
+}
@@ -0,0 +1,179 @@
+//! Runs the game logic in fixed steps, no matter how long each iteration of a frontend's main loop
+//! takes, so a game plays at the same speed on the hardware and in the terminal.
+
+use core::time::Duration;
+
+/// The time between two logic ticks, unless changed using [`GameLoop::tick`]
+pub const TICK: Duration = Duration::from_millis(10);
+
+/// The most ticks run at once to catch up after a slow iteration, unless changed using
+/// [`GameLoop::max_catch_up`]
+pub const MAX_CATCH_UP: u32 = 10;
+
+/// Trait for the system-specific clock driving a [`GameLoop`]
+pub trait TimeSource {
+    /// Time since an arbitrary but fixed point, it must never decrease
+    fn now(&self) -> Duration;
+}
+
+/// Accumulates the time passed on a [`TimeSource`] and hands it out in fixed ticks
+pub struct GameLoop<T: TimeSource> {
+    time: T,
+    last: Duration,
+    /// Time passed that was not handed out as a tick yet
+    accumulator: Duration,
+    tick: Duration,
+    max_catch_up: u32,
+}
+
+impl<T: TimeSource> GameLoop<T> {
+    pub fn new(time: T) -> Self {
+        Self {
+            last: time.now(),
+            time,
+            accumulator: Duration::ZERO,
+            tick: TICK,
+            max_catch_up: MAX_CATCH_UP,
+        }
+    }
+
+    pub fn tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    pub fn max_catch_up(mut self, ticks: u32) -> Self {
+        self.max_catch_up = ticks;
+        self
+    }
+
+    pub fn tick_duration(&self) -> Duration {
+        self.tick
+    }
+
+    pub fn time(&self) -> &T {
+        &self.time
+    }
+
+    /// Adds the time passed since the last call and returns the number of ticks to run now. When
+    /// more than [`GameLoop::max_catch_up`] ticks are due the rest is dropped, so the game slows
+    /// down after a long stall instead of racing to catch up.
+    pub fn advance(&mut self) -> u32 {
+        let now = self.time.now();
+        self.accumulator += now.saturating_sub(self.last);
+        self.last = now;
+
+        let due = (self.accumulator.as_micros() / self.tick.as_micros().max(1)) as u32;
+        let ticks = due.min(self.max_catch_up);
+        self.accumulator = if due > ticks {
+            Duration::ZERO
+        } else {
+            self.accumulator - self.tick * ticks
+        };
+        ticks
+    }
+
+    /// Time left until the next tick is due, e.g. to sleep in the meantime
+    pub fn until_next_tick(&self) -> Duration {
+        let passed = self.accumulator + self.time.now().saturating_sub(self.last);
+        self.tick.saturating_sub(passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+
+    /// A clock that only moves when told to
+    #[derive(Default)]
+    struct FakeClock(Cell<Duration>);
+
+    impl FakeClock {
+        fn advance(&self, millis: u64) {
+            self.0.set(self.0.get() + Duration::from_millis(millis));
+        }
+    }
+
+    impl TimeSource for FakeClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    fn ticks_after(game_loop: &mut GameLoop<FakeClock>, millis: u64) -> u32 {
+        game_loop.time().advance(millis);
+        game_loop.advance()
+    }
+
+    #[test]
+    fn no_ticks_before_any_time_passed() {
+        let mut game_loop = GameLoop::new(FakeClock::default());
+        assert_eq!(game_loop.advance(), 0);
+        assert_eq!(game_loop.until_next_tick(), TICK);
+    }
+
+    #[test]
+    fn starts_counting_at_the_current_time() {
+        let clock = FakeClock::default();
+        clock.advance(5000);
+        let mut game_loop = GameLoop::new(clock);
+        assert_eq!(ticks_after(&mut game_loop, 10), 1);
+    }
+
+    #[test]
+    fn runs_the_ticks_due_and_carries_the_rest_over() {
+        let mut game_loop = GameLoop::new(FakeClock::default());
+        assert_eq!(ticks_after(&mut game_loop, 4), 0);
+        assert_eq!(game_loop.until_next_tick(), Duration::from_millis(6));
+        assert_eq!(ticks_after(&mut game_loop, 6), 1);
+        assert_eq!(ticks_after(&mut game_loop, 25), 2);
+        assert_eq!(game_loop.until_next_tick(), Duration::from_millis(5));
+        assert_eq!(ticks_after(&mut game_loop, 5), 1);
+    }
+
+    #[test]
+    fn fast_iterations_add_up_to_the_same_ticks() {
+        let mut game_loop = GameLoop::new(FakeClock::default());
+        let ticks: u32 = (0..1000).map(|_| ticks_after(&mut game_loop, 3)).sum();
+        assert_eq!(ticks, 300);
+    }
+
+    #[test]
+    fn until_next_tick_counts_time_not_yet_advanced() {
+        let mut game_loop = GameLoop::new(FakeClock::default());
+        game_loop.time().advance(7);
+        assert_eq!(game_loop.until_next_tick(), Duration::from_millis(3));
+        game_loop.time().advance(7);
+        assert_eq!(game_loop.until_next_tick(), Duration::ZERO);
+        assert_eq!(game_loop.advance(), 1);
+    }
+
+    #[test]
+    fn catch_up_is_capped_and_the_excess_dropped() {
+        let mut game_loop = GameLoop::new(FakeClock::default());
+        assert_eq!(ticks_after(&mut game_loop, 125), MAX_CATCH_UP);
+        // the 5ms left over are dropped along with the excess ticks
+        assert_eq!(game_loop.until_next_tick(), TICK);
+        assert_eq!(ticks_after(&mut game_loop, 9), 0);
+        assert_eq!(ticks_after(&mut game_loop, 1), 1);
+    }
+
+    #[test]
+    fn exactly_the_cap_keeps_the_remainder() {
+        let mut game_loop = GameLoop::new(FakeClock::default());
+        assert_eq!(ticks_after(&mut game_loop, 104), MAX_CATCH_UP);
+        assert_eq!(ticks_after(&mut game_loop, 6), 1);
+    }
+
+    #[test]
+    fn tick_and_cap_are_configurable() {
+        let mut game_loop = GameLoop::new(FakeClock::default())
+            .tick(Duration::from_millis(20))
+            .max_catch_up(3);
+        assert_eq!(game_loop.tick_duration(), Duration::from_millis(20));
+        assert_eq!(ticks_after(&mut game_loop, 50), 2);
+        assert_eq!(ticks_after(&mut game_loop, 1000), 3);
+    }
+}
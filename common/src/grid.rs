@@ -0,0 +1,135 @@
+//! A board of cells sized at runtime, so games can adopt the size of the display they run on without
+//! a heap.
+
+/// The most cells a [`Grid`] can hold, enough for a 32x32 display
+pub const MAX_CELLS: usize = 1024;
+
+/// Cells stored row by row in a fixed-capacity array
+#[derive(Clone, Copy, Debug)]
+pub struct Grid<T> {
+    cells: [T; MAX_CELLS],
+    rows: usize,
+    columns: usize,
+}
+
+impl<T: Copy> Grid<T> {
+    /// An empty grid, see [`Grid::resize`]
+    pub const fn new(value: T) -> Self {
+        Self {
+            cells: [value; MAX_CELLS],
+            rows: 0,
+            columns: 0,
+        }
+    }
+
+    /// Changes the size and sets all cells to `value`. Rows that do not fit into [`MAX_CELLS`] are
+    /// left out, with a warning.
+    pub fn resize(&mut self, rows: usize, columns: usize, value: T) {
+        self.columns = columns.min(MAX_CELLS);
+        self.rows = rows.min(MAX_CELLS / self.columns.max(1));
+        if (self.rows, self.columns) != (rows, columns) {
+            log::warn!(
+                "a {rows}x{columns} grid does not fit into {MAX_CELLS} cells, using {}x{}",
+                self.rows,
+                self.columns
+            );
+        }
+        self.fill(value);
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// True if the size is the same as the given one, after leaving out what would not fit
+    pub fn fits(&self, rows: usize, columns: usize) -> bool {
+        let columns = columns.min(MAX_CELLS);
+        self.columns == columns && self.rows == rows.min(MAX_CELLS / columns.max(1))
+    }
+
+    pub fn contains(&self, row: isize, col: isize) -> bool {
+        row >= 0 && col >= 0 && (row as usize) < self.rows && (col as usize) < self.columns
+    }
+
+    /// The cell at the given position, panics if it is outside of the grid
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.cells[self.index(row, col)]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: T) {
+        let index = self.index(row, col);
+        self.cells[index] = value;
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.cells_mut().fill(value);
+    }
+
+    /// Overwrites row `to` with a copy of row `from`
+    pub fn copy_row(&mut self, from: usize, to: usize) {
+        let start = self.index(from, 0);
+        let destination = self.index(to, 0);
+        self.cells
+            .copy_within(start..start + self.columns, destination);
+    }
+
+    /// All cells, row by row
+    pub fn cells(&self) -> &[T] {
+        &self.cells[..self.rows * self.columns]
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells[..self.rows * self.columns]
+    }
+
+    /// Iterates over the positions and values of all cells, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, T)> + '_ {
+        self.cells()
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (i / self.columns, i % self.columns, *cell))
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        assert!(
+            row < self.rows && col < self.columns,
+            "cell outside of the grid"
+        );
+        row * self.columns + col
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_leaves_out_the_rows_that_do_not_fit() {
+        let mut grid = Grid::new(0u8);
+        grid.resize(42, 16, 1);
+        assert_eq!((grid.rows(), grid.columns()), (42, 16));
+        assert!(grid.fits(42, 16));
+
+        grid.resize(100, 16, 1);
+        assert_eq!((grid.rows(), grid.columns()), (MAX_CELLS / 16, 16));
+        assert!(grid.fits(100, 16));
+        assert!(!grid.fits(42, 16));
+        assert_eq!(grid.cells().len(), MAX_CELLS);
+
+        grid.resize(1, MAX_CELLS + 1, 1);
+        assert_eq!((grid.rows(), grid.columns()), (1, MAX_CELLS));
+    }
+
+    #[test]
+    fn copy_row_overwrites_the_destination() {
+        let mut grid = Grid::new(0u8);
+        grid.resize(3, 2, 0);
+        grid.set(0, 1, 7);
+        grid.copy_row(0, 2);
+        assert_eq!(grid.cells(), [0, 7, 0, 0, 0, 7]);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::storage::{Storage, StorageError};
+
+/// Number of scores kept for each game
+pub const HIGH_SCORE_ENTRIES: usize = 5;
+
+/// Number of letters a player enters next to a high score
+pub const INITIALS: usize = 3;
+
+/// Version of the stored format, increased whenever the format changes
+const FORMAT_VERSION: u8 = 1;
+
+/// Stored size of a single entry: the initials followed by the score as a little-endian u32
+const ENTRY_SIZE: usize = INITIALS + 4;
+
+/// Stored size of a whole table: version, number of entries and the entries
+const TABLE_SIZE: usize = 2 + HIGH_SCORE_ENTRIES * ENTRY_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    /// Upper case ASCII letters or spaces
+    pub initials: [u8; INITIALS],
+    pub score: usize,
+}
+
+impl HighScore {
+    pub fn initials(&self) -> &str {
+        core::str::from_utf8(&self.initials).unwrap_or("???")
+    }
+}
+
+/// The best scores of a single game, highest first
+#[derive(Clone, Debug)]
+pub struct HighScores {
+    entries: [HighScore; HIGH_SCORE_ENTRIES],
+    len: usize,
+    /// True when the table changed since it was loaded or saved
+    dirty: bool,
+}
+
+impl HighScores {
+    /// Namespace of the high score records in a [`Storage`]
+    pub const NAMESPACE: &'static str = "scores";
+
+    pub const fn new() -> Self {
+        Self {
+            entries: [HighScore {
+                initials: *b"   ",
+                score: 0,
+            }; HIGH_SCORE_ENTRIES],
+            len: 0,
+            dirty: false,
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries[..self.len]
+    }
+
+    pub fn best(&self) -> Option<usize> {
+        self.entries().first().map(|entry| entry.score)
+    }
+
+    /// True if the score would make it into the table
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0
+            && (self.len < HIGH_SCORE_ENTRIES
+                || self.entries().last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds a score to the table, returns its rank (0 being the best) or `None` if it did not qualify
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+
+        // later entries with the same score rank below the earlier ones
+        let rank = self
+            .entries()
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.len);
+
+        self.len = (self.len + 1).min(HIGH_SCORE_ENTRIES);
+        self.entries[rank..self.len].rotate_right(1);
+        self.entries[rank] = entry;
+        self.dirty = true;
+
+        Some(rank)
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Replaces the table with the one stored for the game `name`, keeps the table if none is stored
+    pub fn load<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+        name: &str,
+    ) -> Result<(), StorageError> {
+        let mut buffer = [0; TABLE_SIZE];
+        if let Some(len) = storage.read(Self::NAMESPACE, name, &mut buffer)? {
+            *self = Self::decode(&buffer[..len]).ok_or(StorageError::Corrupt)?;
+        }
+        Ok(())
+    }
+
+    /// Stores the table for the game `name`. The table counts as saved even if that fails, so a
+    /// failure is reported once and only retried after the next change instead of on every update.
+    pub fn save<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+        name: &str,
+    ) -> Result<(), StorageError> {
+        self.dirty = false;
+        storage.write(Self::NAMESPACE, name, &self.encode())
+    }
+
+    fn encode(&self) -> [u8; TABLE_SIZE] {
+        let mut buffer = [0; TABLE_SIZE];
+        buffer[0] = FORMAT_VERSION;
+        buffer[1] = self.len as u8;
+
+        for (entry, chunk) in self
+            .entries()
+            .iter()
+            .zip(buffer[2..].chunks_mut(ENTRY_SIZE))
+        {
+            let score = u32::try_from(entry.score).unwrap_or(u32::MAX);
+            chunk[..INITIALS].copy_from_slice(&entry.initials);
+            chunk[INITIALS..].copy_from_slice(&score.to_le_bytes());
+        }
+
+        buffer
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        let (&[version, len], entries) = data.split_first_chunk::<2>()?;
+        let len = len as usize;
+        if version != FORMAT_VERSION || len > HIGH_SCORE_ENTRIES || entries.len() < len * ENTRY_SIZE
+        {
+            return None;
+        }
+
+        let mut table = Self::new();
+        for (i, chunk) in entries.chunks_exact(ENTRY_SIZE).take(len).enumerate() {
+            let (initials, score) = chunk.split_first_chunk::<INITIALS>()?;
+            table.entries[i] = HighScore {
+                initials: *initials,
+                score: u32::from_le_bytes(score.try_into().ok()?) as usize,
+            };
+        }
+        table.len = len;
+
+        Some(table)
+    }
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::storage::tests::MemoryStorage;
+
+    fn entry(initials: &[u8; INITIALS], score: usize) -> HighScore {
+        HighScore {
+            initials: *initials,
+            score,
+        }
+    }
+
+    fn table(scores: &[usize]) -> HighScores {
+        let mut table = HighScores::new();
+        for &score in scores {
+            table.insert(entry(b"ABC", score));
+        }
+        table
+    }
+
+    fn stored(data: &[u8]) -> MemoryStorage {
+        let mut storage = MemoryStorage::default();
+        storage.write(HighScores::NAMESPACE, "TEST", data).unwrap();
+        storage
+    }
+
+    #[test]
+    fn insert_keeps_the_best_scores_in_order() {
+        let mut table = table(&[30, 10, 50, 20, 40]);
+        assert!(!table.qualifies(10));
+        assert_eq!(table.insert(entry(b"NEW", 35)), Some(2));
+
+        let scores: Vec<_> = table.entries().iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, [50, 40, 35, 30, 20]);
+        assert_eq!(table.entries()[2].initials(), "NEW");
+    }
+
+    #[test]
+    fn equal_scores_rank_below_earlier_ones() {
+        let mut table = table(&[20]);
+        assert_eq!(table.insert(entry(b"TWO", 20)), Some(1));
+        assert_eq!(table.insert(entry(b"ZRO", 0)), None);
+    }
+
+    #[test]
+    fn saved_table_loads_the_same() {
+        let mut storage = MemoryStorage::default();
+        let mut table = table(&[7, 1_000_000, 42]);
+        assert!(table.is_dirty());
+
+        table.save(&mut storage, "TEST").unwrap();
+        assert!(!table.is_dirty());
+
+        let mut loaded = HighScores::new();
+        loaded.load(&mut storage, "TEST").unwrap();
+        assert_eq!(loaded.entries(), table.entries());
+        assert!(!loaded.is_dirty());
+    }
+
+    #[test]
+    fn failed_save_is_only_retried_after_the_next_change() {
+        let mut storage = MemoryStorage::default();
+        storage.failing = true;
+        let mut table = table(&[7]);
+
+        assert_eq!(table.save(&mut storage, "TEST"), Err(StorageError::Io));
+        assert!(!table.is_dirty());
+
+        table.insert(entry(b"TWO", 2));
+        assert!(table.is_dirty());
+    }
+
+    #[test]
+    fn missing_table_keeps_the_current_one() {
+        let mut table = table(&[5]);
+        table.load(&mut MemoryStorage::default(), "TEST").unwrap();
+        assert_eq!(table.best(), Some(5));
+    }
+
+    #[test]
+    fn scores_beyond_u32_are_stored_saturated() {
+        let mut storage = MemoryStorage::default();
+        table(&[usize::MAX]).save(&mut storage, "TEST").unwrap();
+
+        let mut loaded = HighScores::new();
+        loaded.load(&mut storage, "TEST").unwrap();
+        assert_eq!(loaded.best(), Some(u32::MAX as usize));
+    }
+
+    #[test]
+    fn invalid_tables_are_reported_as_corrupt() {
+        let valid = table(&[3, 2, 1]).encode();
+
+        let mut wrong_version = valid;
+        wrong_version[0] = FORMAT_VERSION + 1;
+
+        let mut too_many = valid;
+        too_many[1] = HIGH_SCORE_ENTRIES as u8 + 1;
+
+        let cases: [&[u8]; 5] = [
+            &[],
+            &valid[..1],
+            &valid[..2 + 3 * ENTRY_SIZE - 1],
+            &wrong_version,
+            &too_many,
+        ];
+        for data in cases {
+            let mut table = table(&[9]);
+            assert_eq!(
+                table.load(&mut stored(data), "TEST"),
+                Err(StorageError::Corrupt),
+                "{data:?}"
+            );
+            assert_eq!(table.best(), Some(9));
+        }
+    }
+}
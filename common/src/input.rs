@@ -1,9 +1,153 @@
+use core::time::Duration;
+
+use crate::buffer::{Event, EventBuffer};
+
 pub trait Input {
     fn left(&self) -> bool;
     fn right(&self) -> bool;
     fn up(&self) -> bool;
     fn down(&self) -> bool;
     fn action(&self) -> bool;
+
+    /// The button events that happened since the previous update, in the order they happened.
+    /// Only available when the input is (directly or through an adapter) fed from an [`EventInput`].
+    fn events(&self) -> &[InputEvent] {
+        &[]
+    }
+
+    /// The gestures recognized during the last update.
+    /// Only available when the input is fed through a [`GestureRecognizer`], e.g. using a [`Controller`].
+    fn gestures(&self) -> &[Gesture] {
+        &[]
+    }
+
+    /// Get the state of a button by value rather than by method
+    fn button(&self, button: Button) -> bool {
+        match button {
+            Button::Left => self.left(),
+            Button::Right => self.right(),
+            Button::Up => self.up(),
+            Button::Down => self.down(),
+            Button::Action => self.action(),
+        }
+    }
+
+    /// True if any of the buttons is set
+    fn any(&self) -> bool {
+        Button::ALL.iter().any(|button| self.button(*button))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Up,
+    Down,
+    Action,
+}
+
+impl Button {
+    pub const ALL: [Button; 5] = [
+        Button::Left,
+        Button::Right,
+        Button::Up,
+        Button::Down,
+        Button::Action,
+    ];
+
+    /// Position of the button in [`Button::ALL`], useful for per-button arrays
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEventKind {
+    Pressed,
+    Released,
+}
+
+/// A change of a single button, with the time it happened since some fixed point (usually the start
+/// of the program)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputEvent {
+    pub button: Button,
+    pub kind: InputEventKind,
+    pub timestamp: Duration,
+}
+
+impl Event for InputEvent {
+    const PLACEHOLDER: Self = InputEvent {
+        button: Button::Action,
+        kind: InputEventKind::Released,
+        timestamp: Duration::ZERO,
+    };
+}
+
+/// The maximum number of events kept for a single update
+pub const FRAME_EVENTS: usize = 16;
+
+/// A fixed-capacity list of input events that frontends push into as buttons change
+pub type InputEventBuffer<const N: usize> = EventBuffer<InputEvent, N>;
+
+/// Turns a stream of input events into the [`Input`] view for games that only care about the state
+/// of the buttons. A press that is released again before the next update still shows as pressed
+/// during that update, so short taps are never lost.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct EventInput {
+    held: [bool; 5],
+    pressed: [bool; 5],
+    frame: InputEventBuffer<FRAME_EVENTS>,
+}
+
+impl EventInput {
+    /// Applies the events that happened since the previous update, in the order they happened
+    pub fn update(&mut self, events: &[InputEvent]) {
+        self.frame.clear();
+
+        // buttons still held from before count as pressed
+        self.pressed = self.held;
+
+        for event in events {
+            let i = event.button.index();
+            match event.kind {
+                InputEventKind::Pressed => {
+                    self.held[i] = true;
+                    self.pressed[i] = true;
+                }
+                InputEventKind::Released => self.held[i] = false,
+            }
+
+            self.frame.push(*event);
+        }
+    }
+}
+
+impl Input for EventInput {
+    fn left(&self) -> bool {
+        self.pressed[Button::Left.index()]
+    }
+
+    fn right(&self) -> bool {
+        self.pressed[Button::Right.index()]
+    }
+
+    fn up(&self) -> bool {
+        self.pressed[Button::Up.index()]
+    }
+
+    fn down(&self) -> bool {
+        self.pressed[Button::Down.index()]
+    }
+
+    fn action(&self) -> bool {
+        self.pressed[Button::Action.index()]
+    }
+
+    fn events(&self) -> &[InputEvent] {
+        self.frame.as_slice()
+    }
 }
 
 /// A struct representing the current state of the input buttons
@@ -88,3 +232,675 @@ impl Input for DebouncedInput {
         self.action
     }
 }
+
+/// Timing of the auto-repeat of a held button
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Repeat {
+    /// Time the button has to be held before it starts repeating
+    pub delay: Duration,
+    /// Time between each repeat after the initial delay
+    pub interval: Duration,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(200),
+            interval: Duration::from_millis(60),
+        }
+    }
+}
+
+/// Like [`DebouncedInput`] it reports the rising edge of each button, but keeps reporting presses at
+/// a steady rate while a button is held (also known as DAS/ARR), which is what games like Tetris and
+/// menus want for the direction buttons.
+#[derive(Clone, Copy, Debug)]
+pub struct RepeatingInput {
+    repeat: [Option<Repeat>; 5],
+    /// For how long each button has been held, `None` if it is released
+    held: [Option<Duration>; 5],
+    /// When the next repeat is due for each held button
+    next_repeat: [Duration; 5],
+    pressed: [bool; 5],
+    events: InputEventBuffer<FRAME_EVENTS>,
+}
+
+impl RepeatingInput {
+    /// Creates an adapter that auto-repeats the direction buttons, but not the action button
+    pub fn new(repeat: Repeat) -> Self {
+        let mut input = Self {
+            repeat: [Some(repeat); 5],
+            held: [None; 5],
+            next_repeat: [Duration::ZERO; 5],
+            pressed: [false; 5],
+            events: InputEventBuffer::new(),
+        };
+        input.repeat[Button::Action.index()] = None;
+        input
+    }
+
+    /// Configures the repeat timing of a single button, `None` disables repeating for it
+    pub fn with_repeat(mut self, button: Button, repeat: Option<Repeat>) -> Self {
+        self.repeat[button.index()] = repeat;
+        self
+    }
+
+    pub fn update<I: Input>(&mut self, input: &I, elapsed: Duration) {
+        self.events.clear();
+        for event in input.events() {
+            self.events.push(*event);
+        }
+
+        for button in Button::ALL {
+            let i = button.index();
+
+            self.pressed[i] = match (input.button(button), self.held[i]) {
+                (false, _) => {
+                    self.held[i] = None;
+                    false
+                }
+                (true, None) => {
+                    // initial press
+                    self.held[i] = Some(Duration::ZERO);
+                    if let Some(repeat) = self.repeat[i] {
+                        self.next_repeat[i] = repeat.delay;
+                    }
+                    true
+                }
+                (true, Some(held)) => {
+                    let held = held + elapsed;
+                    self.held[i] = Some(held);
+
+                    match self.repeat[i] {
+                        Some(repeat) if held >= self.next_repeat[i] => {
+                            // only report a single repeat even if the update was late
+                            while self.next_repeat[i] <= held {
+                                self.next_repeat[i] +=
+                                    repeat.interval.max(Duration::from_millis(1));
+                            }
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+            };
+        }
+    }
+}
+
+impl Default for RepeatingInput {
+    fn default() -> Self {
+        Self::new(Repeat::default())
+    }
+}
+
+impl Input for RepeatingInput {
+    fn left(&self) -> bool {
+        self.pressed[Button::Left.index()]
+    }
+
+    fn right(&self) -> bool {
+        self.pressed[Button::Right.index()]
+    }
+
+    fn up(&self) -> bool {
+        self.pressed[Button::Up.index()]
+    }
+
+    fn down(&self) -> bool {
+        self.pressed[Button::Down.index()]
+    }
+
+    fn action(&self) -> bool {
+        self.pressed[Button::Action.index()]
+    }
+
+    fn events(&self) -> &[InputEvent] {
+        self.events.as_slice()
+    }
+}
+
+/// A set of buttons, e.g. the buttons held down together in a chord
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct ButtonSet(u8);
+
+impl ButtonSet {
+    pub const EMPTY: ButtonSet = ButtonSet(0);
+
+    pub const fn with(self, button: Button) -> Self {
+        Self(self.0 | 1 << button.index())
+    }
+
+    pub const fn without(self, button: Button) -> Self {
+        Self(self.0 & !(1 << button.index()))
+    }
+
+    pub const fn contains(self, button: Button) -> bool {
+        self.0 & 1 << button.index() != 0
+    }
+
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The set of buttons currently pressed in an input
+    pub fn pressed<I: Input + ?Sized>(input: &I) -> Self {
+        Button::ALL
+            .iter()
+            .filter(|b| input.button(**b))
+            .fold(Self::EMPTY, |set, b| set.with(*b))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    /// A button held down for at least [`GestureTimings::long_press`]
+    LongPress(Button),
+    /// A button tapped twice within [`GestureTimings::double_tap`]
+    DoubleTap(Button),
+    /// Two or more buttons pressed within [`GestureTimings::chord`] of each other
+    Chord(ButtonSet),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GestureTimings {
+    pub long_press: Duration,
+    /// Maximum time between releasing the first tap and pressing the second one
+    pub double_tap: Duration,
+    /// Maximum time between the first and the last press of a chord
+    pub chord: Duration,
+}
+
+impl Default for GestureTimings {
+    fn default() -> Self {
+        Self {
+            long_press: Duration::from_millis(800),
+            double_tap: Duration::from_millis(250),
+            chord: Duration::from_millis(100),
+        }
+    }
+}
+
+/// The maximum number of gestures recognized in a single update
+const MAX_GESTURES: usize = 8;
+
+/// Recognizes long-presses, double-taps and chords from the button states of an [`Input`]. Buttons that
+/// are part of a chord do not also produce long-presses or taps.
+#[derive(Clone, Copy, Debug)]
+pub struct GestureRecognizer {
+    timings: GestureTimings,
+    /// For how long each button has been held, `None` if it is released
+    held: [Option<Duration>; 5],
+    /// Time since each button was released after a short tap, `None` if there is no pending tap
+    since_tap: [Option<Duration>; 5],
+    /// Buttons that already produced a gesture during the current press
+    consumed: ButtonSet,
+    /// Time since the first button of the current set of held buttons was pressed
+    since_first_press: Option<Duration>,
+    chord_fired: bool,
+    gestures: [Gesture; MAX_GESTURES],
+    gesture_count: usize,
+}
+
+impl GestureRecognizer {
+    pub fn new(timings: GestureTimings) -> Self {
+        Self {
+            timings,
+            held: [None; 5],
+            since_tap: [None; 5],
+            consumed: ButtonSet::EMPTY,
+            since_first_press: None,
+            chord_fired: false,
+            gestures: [Gesture::Chord(ButtonSet::EMPTY); MAX_GESTURES],
+            gesture_count: 0,
+        }
+    }
+
+    fn emit(&mut self, gesture: Gesture) {
+        if self.gesture_count < MAX_GESTURES {
+            self.gestures[self.gesture_count] = gesture;
+            self.gesture_count += 1;
+        }
+    }
+
+    /// Updates the recognizer with the current button states (not edges) of an input
+    pub fn update<I: Input>(&mut self, input: &I, elapsed: Duration) {
+        self.gesture_count = 0;
+
+        let pressed = ButtonSet::pressed(input);
+
+        // chords: wait for the chord window to pass after the first press to see all buttons involved
+        self.since_first_press = match (pressed.is_empty(), self.since_first_press) {
+            (true, _) => {
+                self.chord_fired = false;
+                None
+            }
+            (false, None) => Some(Duration::ZERO),
+            (false, Some(time)) => Some(time + elapsed),
+        };
+        if let Some(time) = self.since_first_press
+            && !self.chord_fired
+            && time >= self.timings.chord
+        {
+            // only buttons that are all held at the end of the window make up the chord
+            self.chord_fired = true;
+            if pressed.len() >= 2 {
+                self.consumed = pressed;
+                self.emit(Gesture::Chord(pressed));
+            }
+        }
+
+        for button in Button::ALL {
+            let i = button.index();
+
+            self.since_tap[i] = self.since_tap[i]
+                .map(|time| time + elapsed)
+                .filter(|time| *time <= self.timings.double_tap);
+
+            match (pressed.contains(button), self.held[i]) {
+                (true, None) => {
+                    self.held[i] = Some(Duration::ZERO);
+                    if self.since_tap[i].take().is_some() {
+                        self.consumed = self.consumed.with(button);
+                        self.emit(Gesture::DoubleTap(button));
+                    }
+                }
+                (true, Some(held)) => {
+                    let held = held + elapsed;
+                    self.held[i] = Some(held);
+
+                    if held >= self.timings.long_press && !self.consumed.contains(button) {
+                        self.consumed = self.consumed.with(button);
+                        self.emit(Gesture::LongPress(button));
+                    }
+                }
+                (false, Some(_)) => {
+                    self.held[i] = None;
+                    if !self.consumed.contains(button) {
+                        self.since_tap[i] = Some(Duration::ZERO);
+                    }
+                    self.consumed = self.consumed.without(button);
+                }
+                (false, None) => {}
+            }
+        }
+    }
+
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures[..self.gesture_count]
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new(GestureTimings::default())
+    }
+}
+
+/// The complete input pipeline of a player as used by the frontends: input events are turned into
+/// button states, which both feed the gesture recognizer and the auto-repeat of the buttons that
+/// the games see.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Controller {
+    events: EventInput,
+    gestures: GestureRecognizer,
+    repeating: RepeatingInput,
+}
+
+impl Controller {
+    pub fn new(gestures: GestureRecognizer, repeating: RepeatingInput) -> Self {
+        Self {
+            events: EventInput::default(),
+            gestures,
+            repeating,
+        }
+    }
+
+    /// Applies the events that happened since the previous update
+    pub fn update(&mut self, events: &[InputEvent], elapsed: Duration) {
+        self.events.update(events);
+        self.gestures.update(&self.events, elapsed);
+        self.repeating.update(&self.events, elapsed);
+    }
+}
+
+impl Input for Controller {
+    fn left(&self) -> bool {
+        self.repeating.left()
+    }
+
+    fn right(&self) -> bool {
+        self.repeating.right()
+    }
+
+    fn up(&self) -> bool {
+        self.repeating.up()
+    }
+
+    fn down(&self) -> bool {
+        self.repeating.down()
+    }
+
+    fn action(&self) -> bool {
+        self.repeating.action()
+    }
+
+    fn events(&self) -> &[InputEvent] {
+        self.events.events()
+    }
+
+    fn gestures(&self) -> &[Gesture] {
+        self.gestures.gestures()
+    }
+}
+
+/// Input for games with more than one player. Single-player games can keep using the [`Input`] view,
+/// which is the input of the first player.
+pub trait MultiInput: Input {
+    /// The number of players that have an input
+    fn player_count(&self) -> usize;
+
+    /// The input of a single player, `None` if there is no such player
+    fn player(&self, index: usize) -> Option<&dyn Input>;
+}
+
+/// The inputs of a fixed number of players
+#[derive(Clone, Copy, Debug)]
+pub struct Players<I: Input, const N: usize> {
+    players: [I; N],
+}
+
+impl<I: Input, const N: usize> Players<I, N> {
+    pub fn new(players: [I; N]) -> Self {
+        const { assert!(N > 0, "there must be at least one player") };
+
+        Self { players }
+    }
+
+    pub fn player_mut(&mut self, index: usize) -> Option<&mut I> {
+        self.players.get_mut(index)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut I> {
+        self.players.iter_mut()
+    }
+}
+
+impl<I: Input + Default, const N: usize> Default for Players<I, N> {
+    fn default() -> Self {
+        Self::new(core::array::from_fn(|_| I::default()))
+    }
+}
+
+impl<I: Input, const N: usize> Input for Players<I, N> {
+    fn left(&self) -> bool {
+        self.players[0].left()
+    }
+
+    fn right(&self) -> bool {
+        self.players[0].right()
+    }
+
+    fn up(&self) -> bool {
+        self.players[0].up()
+    }
+
+    fn down(&self) -> bool {
+        self.players[0].down()
+    }
+
+    fn action(&self) -> bool {
+        self.players[0].action()
+    }
+
+    fn events(&self) -> &[InputEvent] {
+        self.players[0].events()
+    }
+
+    fn gestures(&self) -> &[Gesture] {
+        self.players[0].gestures()
+    }
+}
+
+impl<I: Input, const N: usize> MultiInput for Players<I, N> {
+    fn player_count(&self) -> usize {
+        N
+    }
+
+    fn player(&self, index: usize) -> Option<&dyn Input> {
+        self.players.get(index).map(|player| player as &dyn Input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    const TICK: u64 = 10;
+
+    use Button::*;
+    use InputEventKind::*;
+
+    /// Runs a controller in ticks of [`TICK`] milliseconds up to `until`, delivering each event with
+    /// the tick at its timestamp, and calls `each` with the time and the controller after every tick
+    fn run(
+        mut controller: Controller,
+        events: &[(u64, Button, InputEventKind)],
+        until: u64,
+        mut each: impl FnMut(u64, &Controller),
+    ) {
+        for time in (0..=until).step_by(TICK as usize) {
+            let due: Vec<_> = events
+                .iter()
+                .filter(|(at, _, _)| (time.saturating_sub(TICK - 1)..=time).contains(at))
+                .map(|&(at, button, kind)| InputEvent {
+                    button,
+                    kind,
+                    timestamp: Duration::from_millis(at),
+                })
+                .collect();
+
+            let elapsed = if time == 0 { 0 } else { TICK };
+            controller.update(&due, Duration::from_millis(elapsed));
+            each(time, &controller);
+        }
+    }
+
+    fn gestures(events: &[(u64, Button, InputEventKind)], until: u64) -> Vec<(u64, Gesture)> {
+        let mut gestures = Vec::new();
+        run(Controller::default(), events, until, |time, controller| {
+            gestures.extend(controller.gestures().iter().map(|gesture| (time, *gesture)));
+        });
+        gestures
+    }
+
+    /// The times at which `button` reads as pressed
+    fn presses(
+        controller: Controller,
+        button: Button,
+        events: &[(u64, Button, InputEventKind)],
+        until: u64,
+    ) -> Vec<u64> {
+        let mut presses = Vec::new();
+        run(controller, events, until, |time, controller| {
+            if controller.button(button) {
+                presses.push(time);
+            }
+        });
+        presses
+    }
+
+    #[test]
+    fn long_press_fires_once_after_the_threshold() {
+        assert_eq!(
+            gestures(&[(0, Action, Pressed), (1500, Action, Released)], 2000),
+            [(800, Gesture::LongPress(Action))]
+        );
+    }
+
+    #[test]
+    fn short_press_is_not_a_long_press() {
+        assert_eq!(
+            gestures(&[(0, Action, Pressed), (790, Action, Released)], 2000),
+            []
+        );
+    }
+
+    #[test]
+    fn double_tap_within_the_window() {
+        assert_eq!(
+            gestures(
+                &[
+                    (0, Up, Pressed),
+                    (50, Up, Released),
+                    (300, Up, Pressed),
+                    (350, Up, Released),
+                ],
+                1000
+            ),
+            [(300, Gesture::DoubleTap(Up))]
+        );
+    }
+
+    #[test]
+    fn taps_too_far_apart_are_no_double_tap() {
+        // the release is seen with the tick after the one it happened in, the window starts there
+        assert_eq!(
+            gestures(
+                &[
+                    (0, Up, Pressed),
+                    (50, Up, Released),
+                    (330, Up, Pressed),
+                    (350, Up, Released),
+                ],
+                1000
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn long_press_is_no_tap() {
+        assert_eq!(
+            gestures(
+                &[
+                    (0, Up, Pressed),
+                    (900, Up, Released),
+                    (950, Up, Pressed),
+                    (1000, Up, Released),
+                ],
+                1500
+            ),
+            [(800, Gesture::LongPress(Up))]
+        );
+    }
+
+    #[test]
+    fn chord_of_buttons_pressed_together() {
+        let chord = ButtonSet::EMPTY.with(Left).with(Right);
+        // held well past the long press threshold, the chord buttons do not also long press
+        assert_eq!(
+            gestures(
+                &[
+                    (0, Left, Pressed),
+                    (50, Right, Pressed),
+                    (1500, Left, Released),
+                    (1500, Right, Released),
+                ],
+                2000
+            ),
+            [(100, Gesture::Chord(chord))]
+        );
+    }
+
+    #[test]
+    fn buttons_pressed_too_far_apart_are_no_chord() {
+        assert_eq!(
+            gestures(
+                &[
+                    (0, Left, Pressed),
+                    (150, Right, Pressed),
+                    (500, Left, Released),
+                    (500, Right, Released),
+                ],
+                1000
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn held_direction_repeats_after_the_delay_at_the_interval() {
+        assert_eq!(
+            presses(
+                Controller::default(),
+                Left,
+                &[(0, Left, Pressed), (490, Left, Released)],
+                1000
+            ),
+            [0, 200, 260, 320, 380, 440]
+        );
+    }
+
+    #[test]
+    fn action_does_not_repeat_by_default() {
+        assert_eq!(
+            presses(
+                Controller::default(),
+                Action,
+                &[(0, Action, Pressed), (600, Action, Released)],
+                1000
+            ),
+            [0]
+        );
+    }
+
+    #[test]
+    fn repeat_timing_is_configurable() {
+        let repeat = Repeat {
+            delay: Duration::from_millis(100),
+            interval: Duration::from_millis(100),
+        };
+        let controller = Controller::new(
+            GestureRecognizer::default(),
+            RepeatingInput::new(Repeat::default()).with_repeat(Action, Some(repeat)),
+        );
+        assert_eq!(
+            presses(
+                controller,
+                Action,
+                &[(0, Action, Pressed), (350, Action, Released)],
+                1000
+            ),
+            [0, 100, 200, 300]
+        );
+    }
+
+    #[test]
+    fn late_update_reports_a_single_repeat() {
+        let mut input = RepeatingInput::default();
+        let held = BasicInput {
+            down: true,
+            ..Default::default()
+        };
+
+        input.update(&held, Duration::ZERO);
+        assert!(input.down());
+
+        // far past the delay and several intervals
+        input.update(&held, Duration::from_millis(500));
+        assert!(input.down());
+
+        // the missed repeats are not made up for
+        input.update(&held, Duration::from_millis(10));
+        assert!(!input.down());
+    }
+}
@@ -0,0 +1,371 @@
+use crate::{
+    display::{Pixel, PixelDisplay},
+    font::Font,
+};
+
+/// Number of empty rows between two lines of wrapped text
+pub const LINE_SPACING: usize = 1;
+
+/// A rectangular area of the display, may extend outside of the display
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub row: isize,
+    pub col: isize,
+    pub height: usize,
+    pub width: usize,
+}
+
+impl Rect {
+    pub const fn new(row: isize, col: isize, height: usize, width: usize) -> Self {
+        Self {
+            row,
+            col,
+            height,
+            width,
+        }
+    }
+
+    /// A rectangle covering the whole display
+    pub fn of(display: &dyn PixelDisplay) -> Self {
+        Self::new(0, 0, display.rows(), display.columns())
+    }
+
+    /// Splits off the top `rows` rows, returning (top, rest)
+    pub fn split_top(self, rows: usize) -> (Rect, Rect) {
+        let rows = rows.min(self.height);
+        (
+            Rect::new(self.row, self.col, rows, self.width),
+            Rect::new(
+                self.row + rows as isize,
+                self.col,
+                self.height - rows,
+                self.width,
+            ),
+        )
+    }
+
+    /// The same rectangle moved by the given number of rows and columns
+    pub fn translate(self, rows: isize, cols: isize) -> Rect {
+        Rect::new(self.row + rows, self.col + cols, self.height, self.width)
+    }
+
+    pub fn contains(&self, row: isize, col: isize) -> bool {
+        row >= self.row
+            && row < self.row + self.height as isize
+            && col >= self.col
+            && col < self.col + self.width as isize
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// Wraps a display and silently drops all pixels outside of the given rectangle
+pub struct Clipped<'a> {
+    display: &'a mut dyn PixelDisplay,
+    clip: Rect,
+}
+
+impl<'a> Clipped<'a> {
+    pub fn new(display: &'a mut dyn PixelDisplay, clip: Rect) -> Self {
+        Self { display, clip }
+    }
+}
+
+impl PixelDisplay for Clipped<'_> {
+    fn rows(&self) -> usize {
+        self.display.rows()
+    }
+
+    fn columns(&self) -> usize {
+        self.display.columns()
+    }
+
+    fn set_pixel(&mut self, row: usize, col: usize, value: Pixel) {
+        if self.clip.contains(row as isize, col as isize) {
+            self.display.set_pixel(row, col, value);
+        }
+    }
+}
+
+/// Returns the width in pixels of a single line of text, not counting the spacing after the last glyph
+pub fn text_width(font: &dyn Font, text: &str) -> usize {
+    let mut width = 0;
+    let mut last_spacing = 0;
+
+    for c in text.chars() {
+        let glyph = font.glyph(c);
+        width += glyph.advance as usize;
+        last_spacing = glyph.advance.saturating_sub(glyph.width) as usize;
+    }
+
+    width - last_spacing
+}
+
+/// The number of rows each line of wrapped text occupies
+pub fn line_height(font: &dyn Font) -> usize {
+    font.height() + LINE_SPACING
+}
+
+/// Returns the first font in `fonts` where `text` fits within `width`, or the last font if none fit
+pub fn fitting_font<'f>(fonts: &[&'f dyn Font], text: &str, width: usize) -> &'f dyn Font {
+    fonts
+        .iter()
+        .find(|font| text_width(**font, text) <= width)
+        .or(fonts.last())
+        .copied()
+        .expect("at least one font must be given")
+}
+
+/// Draws a single line of text at the top of `rect`, aligned horizontally and clipped to the rectangle
+pub fn draw_aligned(
+    display: &mut dyn PixelDisplay,
+    font: &dyn Font,
+    rect: Rect,
+    align: Align,
+    text: &str,
+) {
+    let width = text_width(font, text) as isize;
+    let col = match align {
+        Align::Left => rect.col,
+        Align::Center => rect.col + (rect.width as isize - width) / 2,
+        Align::Right => rect.col + rect.width as isize - width,
+    };
+
+    Clipped::new(display, rect).draw_text_with(font, rect.row, col, text);
+}
+
+/// Splits text into lines no wider than `width`, breaking between words where possible and inside
+/// words that are too long to fit on a line of their own
+pub fn wrap<'a, 'f>(font: &'f dyn Font, text: &'a str, width: usize) -> Wrap<'a, 'f> {
+    Wrap {
+        font,
+        remaining: text,
+        width,
+    }
+}
+
+/// Iterator over the lines of wrapped text, see [`wrap`]
+pub struct Wrap<'a, 'f> {
+    font: &'f dyn Font,
+    remaining: &'a str,
+    width: usize,
+}
+
+impl<'a> Iterator for Wrap<'a, '_> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // spaces left over from breaking the previous line never start a new one
+        let text = self.remaining.trim_start_matches(' ');
+        if text.is_empty() {
+            return None;
+        }
+
+        let mut last_space = None;
+        let mut width = 0;
+
+        for (index, c) in text.char_indices() {
+            if c == '\n' {
+                self.remaining = &text[index + 1..];
+                return Some(text[..index].trim_end());
+            }
+
+            if c == ' ' {
+                last_space = Some(index);
+            }
+
+            // a glyph that does not fit ends the line (spaces may hang over the edge), but always
+            // keep at least one character per line to guarantee progress
+            let glyph = self.font.glyph(c);
+            if c != ' ' && index > 0 && width + glyph.width as usize > self.width {
+                let end = last_space.unwrap_or(index);
+                self.remaining = &text[end..];
+                return Some(text[..end].trim_end());
+            }
+
+            width += glyph.advance as usize;
+        }
+
+        self.remaining = "";
+        Some(text.trim_end())
+    }
+}
+
+/// Number of lines of text that fit vertically within `rect`, always at least one
+pub fn lines_per_page(font: &dyn Font, rect: Rect) -> usize {
+    // the spacing after the last line is not needed
+    ((rect.height + LINE_SPACING) / line_height(font)).max(1)
+}
+
+/// The number of pages needed to show all of the wrapped text within `rect`
+pub fn page_count(font: &dyn Font, rect: Rect, text: &str) -> usize {
+    wrap(font, text, rect.width)
+        .count()
+        .div_ceil(lines_per_page(font, rect))
+}
+
+/// Draws the wrapped text within `rect`, returns the number of lines drawn
+pub fn draw_wrapped(
+    display: &mut dyn PixelDisplay,
+    font: &dyn Font,
+    rect: Rect,
+    align: Align,
+    text: &str,
+) -> usize {
+    draw_page(display, font, rect, align, text, 0)
+}
+
+/// Draws one page of wrapped text within `rect`, returns the number of lines drawn (zero if the page
+/// does not exist)
+pub fn draw_page(
+    display: &mut dyn PixelDisplay,
+    font: &dyn Font,
+    rect: Rect,
+    align: Align,
+    text: &str,
+    page: usize,
+) -> usize {
+    let per_page = lines_per_page(font, rect);
+    let mut drawn = 0;
+
+    for (i, line) in wrap(font, text, rect.width)
+        .skip(page * per_page)
+        .take(per_page)
+        .enumerate()
+    {
+        let line_rect = Rect::new(
+            rect.row + (i * line_height(font)) as isize,
+            rect.col,
+            font.height(),
+            rect.width,
+        );
+        draw_aligned(display, font, line_rect, align, line);
+        drawn += 1;
+    }
+
+    drawn
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::font::Glyph;
+
+    /// Letters 3 columns wide with 1 column of spacing, `W` 5 wide and spaces 2 wide, 5 rows high
+    struct TestFont;
+
+    impl Font for TestFont {
+        fn height(&self) -> usize {
+            5
+        }
+
+        fn baseline(&self) -> usize {
+            4
+        }
+
+        fn glyph(&self, character: char) -> Glyph {
+            let (width, advance) = match character {
+                ' ' => (0, 2),
+                'W' => (5, 6),
+                _ => (3, 4),
+            };
+            Glyph {
+                rows: &[],
+                skip: 0,
+                width,
+                advance,
+            }
+        }
+    }
+
+    fn lines(text: &str, width: usize) -> Vec<&str> {
+        wrap(&TestFont, text, width).collect()
+    }
+
+    #[test]
+    fn text_width_of_lines() {
+        let cases = [
+            ("", 0),
+            ("A", 3),
+            ("AB", 7),
+            ("W", 5),
+            ("AW", 9),
+            ("WA", 9),
+            ("AB CD", 17),
+            ("A ", 4),
+            (" ", 0),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(text_width(&TestFont, text), expected, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn wrap_breaks_between_words() {
+        let cases: [(&str, usize, &[&str]); 12] = [
+            ("", 10, &[]),
+            ("   ", 10, &[]),
+            ("AB CD", 17, &["AB CD"]),
+            ("AB CD", 16, &["AB", "CD"]),
+            ("AB CD EF", 11, &["AB", "CD", "EF"]),
+            ("AB   CD", 9, &["AB", "CD"]),
+            ("  AB  ", 10, &["AB"]),
+            ("AB\nCD", 100, &["AB", "CD"]),
+            ("AB\n\nCD", 100, &["AB", "", "CD"]),
+            ("AB \nCD", 100, &["AB", "CD"]),
+            // a trailing space may hang over the edge
+            ("AB C", 7, &["AB", "C"]),
+            ("WW W", 11, &["WW", "W"]),
+        ];
+        for (text, width, expected) in cases {
+            assert_eq!(lines(text, width), expected, "{text:?} in {width}");
+        }
+    }
+
+    #[test]
+    fn words_longer_than_the_width_are_broken() {
+        let cases: [(&str, usize, &[&str]); 5] = [
+            ("ABCDEFG", 11, &["ABC", "DEF", "G"]),
+            ("ABCDEFG", 10, &["AB", "CD", "EF", "G"]),
+            ("A BCDEFG H", 11, &["A", "BCD", "EFG", "H"]),
+            // at least one character per line, even if it does not fit
+            ("WAW", 4, &["W", "A", "W"]),
+            ("AB", 0, &["A", "B"]),
+        ];
+        for (text, width, expected) in cases {
+            assert_eq!(lines(text, width), expected, "{text:?} in {width}");
+        }
+    }
+
+    #[test]
+    fn wrapped_lines_fit_the_width() {
+        let text = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG WWWWWWW";
+        for width in 5..60 {
+            for line in lines(text, width) {
+                assert!(text_width(&TestFont, line) <= width, "{line:?} in {width}");
+            }
+        }
+    }
+
+    #[test]
+    fn pages() {
+        // lines are 5 rows high with a row of spacing in between
+        let page = |height| Rect::new(0, 0, height, 11);
+        assert_eq!(lines_per_page(&TestFont, page(0)), 1);
+        assert_eq!(lines_per_page(&TestFont, page(10)), 1);
+        assert_eq!(lines_per_page(&TestFont, page(11)), 2);
+
+        assert_eq!(page_count(&TestFont, page(11), ""), 0);
+        assert_eq!(page_count(&TestFont, page(11), "AB CD"), 1);
+        assert_eq!(page_count(&TestFont, page(11), "AB CD EF"), 2);
+    }
+}
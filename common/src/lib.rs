@@ -1,31 +1,198 @@
 #![no_std]
 
-use core::time::Duration;
+use core::{ops::Range, time::Duration};
 
 use display::PixelDisplay;
+use events::GameEvent;
+use font::Glyph;
+use highscore::HighScores;
 use input::Input;
+use orientation::Orientation;
+use settings::Difficulty;
+use snapshot::{Reader, SnapshotError, Writer};
 
+pub mod audio;
+pub mod buffer;
 pub mod display;
+pub mod events;
+pub mod font;
+pub mod font_large_digits;
 pub mod font_monospace;
+pub mod font_tiny;
+pub mod framebuffer;
+pub mod game_loop;
+pub mod grid;
+pub mod highscore;
 pub mod input;
+pub mod layout;
+pub mod marquee;
 pub mod menu;
+pub mod number;
+pub mod orientation;
+pub mod random;
+pub mod settings;
+pub mod shell;
 pub mod snake;
+pub mod snapshot;
+pub mod storage;
 pub mod tetris;
 
 /// Trait for system-specific generation of a seed for the random number generator
 pub trait RandomNumberSource {
     fn next_u32(&mut self) -> u32;
+
+    /// Returns a uniformly distributed number in `range`, without the bias of a plain modulo.
+    /// Returns `range.start` for an empty range.
+    fn next_range(&mut self, range: Range<u32>) -> u32 {
+        let span = range.end.saturating_sub(range.start);
+        if span == 0 {
+            return range.start;
+        }
+
+        // reject the values at the bottom that would make some results more likely than others
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let value = self.next_u32();
+            if value >= threshold {
+                return range.start + value % span;
+            }
+        }
+    }
+
+    /// Returns a uniformly distributed index in `0..len`, useful for picking from a slice
+    fn next_index(&mut self, len: usize) -> usize {
+        self.next_range(0..len as u32) as usize
+    }
+
+    fn next_bool(&mut self) -> bool {
+        self.next_u32() & 1 != 0
+    }
+
+    /// Shuffles the slice in place using the Fisher-Yates algorithm
+    fn shuffle<T>(&mut self, items: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..items.len()).rev() {
+            let j = self.next_index(i + 1);
+            items.swap(i, j);
+        }
+    }
 }
 
-pub trait Game<I: Input, D: PixelDisplay, R: RandomNumberSource> {
-    /// Runs the logic of the game given the current state of the input and shows it to the display
-    /// Should always draw the current state to the display in immediate-mode like style.
-    fn update(&mut self, elapsed: Duration, input: &I, display: &mut D, random: &mut R);
+pub trait Game<I: Input, R: RandomNumberSource> {
+    /// Runs the logic of the game given the current state of the input. The display is only passed
+    /// for its size, which the game adapts to, drawing happens in [`Game::render`].
+    fn update(&mut self, elapsed: Duration, input: &I, display: &dyn PixelDisplay, random: &mut R);
+
+    /// Draws the current state of the game to the display in immediate-mode like style, i.e. the
+    /// whole frame every time
+    fn render(&mut self, display: &mut dyn PixelDisplay);
+
+    /// True if the frame changed since the last [`Game::render`], so frontends can skip rendering
+    /// and refreshing the display while nothing moves
+    fn is_dirty(&self) -> bool {
+        true
+    }
+
+    /// The events emitted during the last update, see [`events`]. Frontends need to look at them
+    /// after every update, as they are replaced by the next one.
+    fn events(&self) -> &[GameEvent] {
+        &[]
+    }
 
     /// Get the current state of the game
     fn state(&self) -> GameState {
         GameState::Start
     }
+
+    /// The name of the game as shown in the menu
+    fn name(&self) -> &'static str;
+
+    /// A small picture of the game as shown in the menu
+    fn icon(&self) -> Icon;
+
+    /// The score of the current (or last) round, `None` for games that do not keep a score
+    fn score(&self) -> Option<usize> {
+        None
+    }
+
+    /// The best scores of the game, `None` for games that do not keep high scores
+    fn high_scores(&self) -> Option<&HighScores> {
+        None
+    }
+
+    fn high_scores_mut(&mut self) -> Option<&mut HighScores> {
+        None
+    }
+
+    /// The difficulty the game is played at, `None` for games without difficulty levels
+    fn difficulty(&self) -> Option<Difficulty> {
+        None
+    }
+
+    /// Changes the difficulty, only called while the game is not being played
+    fn set_difficulty(&mut self, _difficulty: Difficulty) {}
+
+    /// The orientation the game is shown in, so the menu can read the buttons on the game's screens
+    /// the way the game does (see [`orientation::OrientedGame`])
+    fn orientation(&self) -> Orientation {
+        Orientation::default()
+    }
+
+    /// Writes a snapshot of the round in progress (see [`snapshot`]), returns false if there is no
+    /// round to save or the game does not support it
+    fn save(&self, _writer: &mut Writer) -> Result<bool, SnapshotError> {
+        Ok(false)
+    }
+
+    /// Continues a round from a snapshot written by [`Game::save`]
+    fn restore(&mut self, _reader: &mut Reader) -> Result<(), SnapshotError> {
+        Err(SnapshotError::Unsupported)
+    }
+
+    /// Puts the game back into its initial state, e.g. when restarting or quitting a paused game
+    fn reset(&mut self);
+
+    /// Called when the game gets selected in the menu
+    fn on_enter(&mut self) {}
+
+    /// Called when another game gets selected in the menu
+    fn on_exit(&mut self) {}
+
+    /// Called when the game is paused, it does not receive any updates until it is resumed or reset
+    fn on_pause(&mut self) {}
+
+    /// Called when the game continues after being paused
+    fn on_resume(&mut self) {}
+}
+
+/// A small bitmap identifying a game
+#[derive(Clone, Copy, Debug)]
+pub struct Icon {
+    /// One byte per row, the least significant bit is the leftmost column
+    pub rows: &'static [u8],
+    pub width: u8,
+}
+
+impl Icon {
+    pub const fn new(rows: &'static [u8], width: u8) -> Self {
+        Self { rows, width }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The icon as a glyph, so it can be drawn using [`PixelDisplay::draw_glyph`]
+    pub fn glyph(&self) -> Glyph {
+        Glyph {
+            rows: self.rows,
+            skip: 0,
+            width: self.width,
+            advance: self.width,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,6 +206,7 @@ pub struct TickerGame {
     time: Duration,
     row: usize,
     col: usize,
+    dirty: bool,
 }
 
 impl TickerGame {
@@ -47,6 +215,7 @@ impl TickerGame {
             time: Duration::from_millis(0),
             row: 0,
             col: 0,
+            dirty: true,
         }
     }
 }
@@ -57,8 +226,14 @@ impl Default for TickerGame {
     }
 }
 
-impl<I: Input, D: PixelDisplay, R: RandomNumberSource> Game<I, D, R> for TickerGame {
-    fn update(&mut self, elapsed: Duration, input: &I, display: &mut D, _random: &mut R) {
+impl<I: Input, R: RandomNumberSource> Game<I, R> for TickerGame {
+    fn update(
+        &mut self,
+        elapsed: Duration,
+        input: &I,
+        display: &dyn PixelDisplay,
+        _random: &mut R,
+    ) {
         self.time += elapsed;
 
         if self.time > Duration::from_millis(200) {
@@ -71,11 +246,120 @@ impl<I: Input, D: PixelDisplay, R: RandomNumberSource> Game<I, D, R> for TickerG
             } else {
                 self.col = self.col.saturating_sub(1);
             }
+            self.dirty = true;
         }
+    }
 
+    fn render(&mut self, display: &mut dyn PixelDisplay) {
         display.clear();
         display.set_pixel(self.row, self.col, display::Pixel::On);
 
         display.draw_text(0, 0, "HELLO!");
+        self.dirty = false;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn state(&self) -> GameState {
+        GameState::Playing
+    }
+
+    fn name(&self) -> &'static str {
+        "TICKER"
+    }
+
+    fn icon(&self) -> Icon {
+        Icon::new(&[0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80], 8)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::Pcg32;
+
+    /// Returns the given values in order
+    struct Script<'a> {
+        values: &'a [u32],
+        calls: usize,
+    }
+
+    impl RandomNumberSource for Script<'_> {
+        fn next_u32(&mut self) -> u32 {
+            let value = self.values[self.calls];
+            self.calls += 1;
+            value
+        }
+    }
+
+    #[test]
+    fn next_range_stays_within_bounds() {
+        let mut rng = Pcg32::new(7);
+        for range in [
+            0..1,
+            0..2,
+            5..8,
+            10..1000,
+            0..u32::MAX,
+            u32::MAX - 3..u32::MAX,
+        ] {
+            for _ in 0..1000 {
+                assert!(range.contains(&rng.next_range(range.clone())));
+            }
+        }
+        for len in [1, 3, 16, 42] {
+            for _ in 0..1000 {
+                assert!(rng.next_index(len) < len);
+            }
+        }
+    }
+
+    #[test]
+    fn next_range_returns_the_start_of_an_empty_range() {
+        let mut rng = Script {
+            values: &[],
+            calls: 0,
+        };
+        assert_eq!(rng.next_range(5..5), 5);
+        let (start, end) = (9, 2);
+        assert_eq!(rng.next_range(start..end), 9);
+        assert_eq!(rng.calls, 0);
+    }
+
+    #[test]
+    fn next_range_rejects_values_that_would_bias_the_result() {
+        // with a span of 2^31 + 1 only the values from 2^31 - 1 upwards are accepted
+        let span = 0x8000_0001;
+        let mut rng = Script {
+            values: &[0, 0x7FFF_FFFE, u32::MAX],
+            calls: 0,
+        };
+        assert_eq!(rng.next_range(10..10 + span), 10 + (u32::MAX - span));
+        assert_eq!(rng.calls, 3);
+
+        let mut rng = Script {
+            values: &[0x7FFF_FFFF],
+            calls: 0,
+        };
+        assert_eq!(rng.next_range(0..span), 0x7FFF_FFFF);
+        assert_eq!(rng.calls, 1);
+    }
+
+    #[test]
+    fn shuffle_keeps_all_items() {
+        let mut rng = Pcg32::new(3);
+        let mut items = [0, 1, 2, 3, 4, 5, 6, 7];
+        rng.shuffle(&mut items);
+
+        let mut sorted = items;
+        sorted.sort();
+        assert_eq!(sorted, [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_ne!(items, sorted);
     }
 }
@@ -0,0 +1,296 @@
+use core::time::Duration;
+
+use crate::{
+    display::PixelDisplay,
+    font::{Font, Monospace},
+    layout::{self, Align, Clipped, Rect},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollDirection {
+    /// Text moves from right to left on a single line
+    Horizontal,
+    /// Text is wrapped to the width of the area and moves from the bottom to the top
+    Vertical,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollMode {
+    /// Scroll the text through the area over and over again
+    Loop,
+    /// Scroll the text through the area a single time, then stay empty
+    Once,
+    /// Scroll back and forth between showing the start and the end of the text
+    Bounce,
+}
+
+/// Scrolls text that does not fit within an area of the display. Text that fits is shown centered
+/// without scrolling.
+pub struct Marquee<'a> {
+    text: &'a str,
+    font: &'a dyn Font,
+    area: Rect,
+    direction: ScrollDirection,
+    mode: ScrollMode,
+    /// Scrolling speed in pixels per second
+    speed: u32,
+    /// Time spent in the current pass over the text
+    pass_time: Duration,
+    reversed: bool,
+    finished: bool,
+}
+
+impl<'a> Marquee<'a> {
+    pub fn new(text: &'a str, area: Rect) -> Self {
+        Self {
+            text,
+            font: &Monospace,
+            area,
+            direction: ScrollDirection::Horizontal,
+            mode: ScrollMode::Loop,
+            speed: 10,
+            pass_time: Duration::ZERO,
+            reversed: false,
+            finished: false,
+        }
+    }
+
+    pub fn font(mut self, font: &'a dyn Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn direction(mut self, direction: ScrollDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn mode(mut self, mode: ScrollMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the scrolling speed in pixels per second
+    pub fn speed(mut self, pixels_per_second: u32) -> Self {
+        self.speed = pixels_per_second.max(1);
+        self
+    }
+
+    /// Starts scrolling from the beginning again
+    pub fn restart(&mut self) {
+        self.pass_time = Duration::ZERO;
+        self.reversed = false;
+        self.finished = false;
+    }
+
+    /// Replaces the text and starts scrolling from the beginning
+    pub fn set_text(&mut self, text: &'a str) {
+        self.text = text;
+        self.restart();
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// True when a [`ScrollMode::Once`] marquee has scrolled all of the text out of the area, or
+    /// immediately if the text fits without scrolling. Never true for the other modes.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Length of the text along the scroll direction in pixels
+    fn content_length(&self) -> usize {
+        match self.direction {
+            ScrollDirection::Horizontal => layout::text_width(self.font, self.text),
+            ScrollDirection::Vertical => {
+                let lines = layout::wrap(self.font, self.text, self.area.width).count();
+                (lines * layout::line_height(self.font)).saturating_sub(1)
+            }
+        }
+    }
+
+    /// Length of the area along the scroll direction in pixels
+    fn area_length(&self) -> usize {
+        match self.direction {
+            ScrollDirection::Horizontal => self.area.width,
+            ScrollDirection::Vertical => self.area.height,
+        }
+    }
+
+    fn fits(&self) -> bool {
+        self.content_length() <= self.area_length()
+    }
+
+    /// Number of pixels the text moves during one pass
+    fn pass_length(&self) -> usize {
+        match self.mode {
+            ScrollMode::Loop | ScrollMode::Once => self.area_length() + self.content_length(),
+            ScrollMode::Bounce => self.content_length().saturating_sub(self.area_length()),
+        }
+    }
+
+    /// Time a pass takes, at least a microsecond even if the speed makes it shorter
+    fn pass_duration(&self) -> Duration {
+        Duration::from_micros(self.pass_length() as u64 * 1_000_000 / self.speed as u64)
+            .max(Duration::from_micros(1))
+    }
+
+    /// Current position within the pass in pixels
+    fn position(&self) -> usize {
+        let position = (self.pass_time.as_micros() * self.speed as u128 / 1_000_000) as usize;
+        position.min(self.pass_length())
+    }
+
+    /// Scrolls the text, returns true if it moved (or disappeared) so it needs to be drawn again
+    pub fn update(&mut self, elapsed: Duration) -> bool {
+        if self.fits() {
+            self.finished = self.mode == ScrollMode::Once;
+            return false;
+        }
+
+        if self.finished {
+            return false;
+        }
+
+        let offset = self.offset();
+        self.scroll(elapsed);
+        self.finished || self.offset() != offset
+    }
+
+    fn scroll(&mut self, elapsed: Duration) {
+        self.pass_time += elapsed;
+
+        let pass_duration = self.pass_duration();
+        if self.pass_time < pass_duration {
+            return;
+        }
+
+        // skip all passes completed at once, there can be many of them at high speeds
+        let passes = self.pass_time.as_nanos() / pass_duration.as_nanos();
+        match self.mode {
+            ScrollMode::Once => {
+                self.pass_time = pass_duration;
+                self.finished = true;
+            }
+            ScrollMode::Loop | ScrollMode::Bounce => {
+                self.pass_time = Duration::from_nanos(
+                    (self.pass_time.as_nanos() % pass_duration.as_nanos()) as u64,
+                );
+                if self.mode == ScrollMode::Bounce && passes % 2 == 1 {
+                    self.reversed = !self.reversed;
+                }
+            }
+        }
+    }
+
+    /// Offset of the start of the text relative to the start of the area, along the scroll direction
+    fn offset(&self) -> isize {
+        let position = self.position() as isize;
+
+        match self.mode {
+            ScrollMode::Loop | ScrollMode::Once => self.area_length() as isize - position,
+            ScrollMode::Bounce if self.reversed => position - self.pass_length() as isize,
+            ScrollMode::Bounce => -position,
+        }
+    }
+
+    pub fn draw(&self, display: &mut dyn PixelDisplay) {
+        let area = self.area;
+
+        if self.fits() {
+            match self.direction {
+                ScrollDirection::Horizontal => {
+                    layout::draw_aligned(display, self.font, area, Align::Center, self.text)
+                }
+                ScrollDirection::Vertical => {
+                    layout::draw_wrapped(display, self.font, area, Align::Center, self.text);
+                }
+            }
+            return;
+        }
+
+        if self.finished {
+            return;
+        }
+
+        let offset = self.offset();
+        match self.direction {
+            ScrollDirection::Horizontal => {
+                Clipped::new(display, area).draw_text_with(
+                    self.font,
+                    area.row,
+                    area.col + offset,
+                    self.text,
+                );
+            }
+            ScrollDirection::Vertical => {
+                let line_height = layout::line_height(self.font);
+                for (i, line) in layout::wrap(self.font, self.text, area.width).enumerate() {
+                    let row = area.row + offset + (i * line_height) as isize;
+                    if row + line_height as isize <= area.row
+                        || row >= area.row + area.height as isize
+                    {
+                        continue;
+                    }
+
+                    let line_area = Rect::new(row, area.col, self.font.height(), area.width);
+                    layout::draw_aligned(
+                        &mut Clipped::new(display, area),
+                        self.font,
+                        line_area,
+                        Align::Center,
+                        line,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marquee(mode: ScrollMode, speed: u32) -> Marquee<'static> {
+        Marquee::new("TOO LONG TO FIT", Rect::new(0, 0, 9, 10))
+            .mode(mode)
+            .speed(speed)
+    }
+
+    #[test]
+    fn very_high_speeds_do_not_stall_the_update() {
+        for mode in [ScrollMode::Loop, ScrollMode::Bounce, ScrollMode::Once] {
+            let mut marquee = marquee(mode, u32::MAX);
+            assert_eq!(marquee.pass_duration(), Duration::from_micros(1));
+
+            marquee.update(Duration::from_secs(3600));
+            assert!(marquee.pass_time <= marquee.pass_duration(), "{mode:?}");
+            assert_eq!(marquee.is_finished(), mode == ScrollMode::Once);
+        }
+    }
+
+    #[test]
+    fn bounce_turns_after_every_pass() {
+        let mut marquee = marquee(ScrollMode::Bounce, 10);
+        let pass = marquee.pass_duration();
+
+        marquee.update(pass);
+        assert!(marquee.reversed);
+        marquee.update(pass * 2 + pass / 2);
+        assert!(marquee.reversed);
+        assert_eq!(marquee.pass_time, pass / 2);
+        marquee.update(pass * 3);
+        assert!(!marquee.reversed);
+    }
+
+    #[test]
+    fn loop_starts_over_after_a_pass() {
+        let mut marquee = marquee(ScrollMode::Loop, 10);
+        let pass = marquee.pass_duration();
+
+        marquee.update(pass * 5 + Duration::from_millis(100));
+        assert_eq!(marquee.pass_time, Duration::from_millis(100));
+        assert!(!marquee.is_finished());
+    }
+}
@@ -1,63 +1,1238 @@
 use core::time::Duration;
 
-use crate::{Game, GameState, RandomNumberSource, display::PixelDisplay, input::Input};
+use crate::{
+    Game, GameState, Icon, RandomNumberSource,
+    display::{Pixel, PixelDisplay},
+    events::{GameEvent, GameEventBuffer},
+    font::{Font, Monospace, Tiny},
+    highscore::HighScores,
+    input::{Button, ButtonSet, Gesture, Input},
+    layout::{self, Align, LINE_SPACING, Rect},
+    marquee::{Marquee, ScrollMode},
+    number::Number,
+    orientation::OrientedInput,
+    settings::{ATTRACT_TIMEOUTS, Difficulty, RefreshMode, Settings},
+    snapshot::{Reader, SnapshotError, Writer},
+    storage::{Storage, StorageError},
+};
 
-pub struct GameMenu<'a, I: Input, D: PixelDisplay, R: RandomNumberSource> {
-    games: &'a mut [&'a mut dyn Game<I, D, R>],
+/// Namespace and name of the record holding the saved game in a [`Storage`]
+const SAVE_NAMESPACE: &str = "saves";
+const SAVE_NAME: &str = "current";
+
+/// Largest saved game record, the snapshot of a game has to fit into it together with the game's name
+pub const MAX_SAVE_SIZE: usize = 2048;
+
+/// The gesture that pauses a running game: up and down pressed together. No game needs both
+/// directions at once, and unlike a long press it does not hold back the presses a game reacts to.
+pub const PAUSE_GESTURE: Gesture =
+    Gesture::Chord(ButtonSet::EMPTY.with(Button::Up).with(Button::Down));
+
+/// What the saved game record holds, as far as the menu knows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SavedRound {
+    /// Nothing saved by the menu
+    None,
+    /// The current round, as saved by [`GameMenu::save_game`]
+    Current,
+    /// A round that went on or ended after it was saved, it must not be continued
+    Outdated,
+}
+
+/// The choices shown while a game is paused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseOption {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseOption {
+    pub const ALL: [PauseOption; 3] =
+        [PauseOption::Resume, PauseOption::Restart, PauseOption::Quit];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseOption::Resume => "RESUME",
+            PauseOption::Restart => "RESTART",
+            PauseOption::Quit => "QUIT",
+        }
+    }
+}
+
+/// The pause screen shown on top of a frozen game, the options are cycled using up & down
+struct PauseOverlay {
+    selected: usize,
+    title: Marquee<'static>,
+    option: Marquee<'static>,
+}
+
+impl PauseOverlay {
+    fn new(display: &dyn PixelDisplay) -> Self {
+        let (title, rest) = Rect::of(display).split_top(layout::line_height(&Monospace));
+        Self {
+            selected: 0,
+            title: Marquee::new("PAUSE", title).font(&Monospace),
+            option: Marquee::new(PauseOption::ALL[0].label(), rest).font(&Tiny),
+        }
+    }
+
+    fn selected(&self) -> PauseOption {
+        PauseOption::ALL[self.selected]
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.option.set_text(self.selected().label());
+    }
+
+    /// Handles the input and returns the option that was confirmed with the action button, if any
+    fn update<I: Input>(&mut self, input: &I) -> Option<PauseOption> {
+        let count = PauseOption::ALL.len();
+        if input.up() {
+            self.select((self.selected + count - 1) % count);
+        }
+        if input.down() {
+            self.select((self.selected + 1) % count);
+        }
+
+        input.action().then(|| self.selected())
+    }
+
+    /// Scrolls the texts, returns true if they moved
+    fn animate(&mut self, elapsed: Duration) -> bool {
+        // both marquees have to be updated, so no short-circuiting
+        self.title.update(elapsed) | self.option.update(elapsed)
+    }
+
+    fn draw(&self, display: &mut dyn PixelDisplay) {
+        display.clear();
+        self.title.draw(display);
+        self.option.draw(display);
+    }
+}
+
+/// The options on the settings screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SettingsItem {
+    Difficulty,
+    Refresh,
+    Attract,
+    Sound,
+}
+
+impl SettingsItem {
+    /// All options, the difficulty is left out for games without difficulty levels
+    const ALL: [SettingsItem; 4] = [
+        SettingsItem::Difficulty,
+        SettingsItem::Refresh,
+        SettingsItem::Attract,
+        SettingsItem::Sound,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SettingsItem::Difficulty => "LEVEL",
+            SettingsItem::Refresh => "REFRESH",
+            SettingsItem::Attract => "ATTRACT",
+            SettingsItem::Sound => "SOUND",
+        }
+    }
+}
+
+/// Shows one option at a time: its name on top and its value below. Up & down go through the
+/// options, left & right change the value.
+struct SettingsScreen {
+    selected: usize,
+    label: Marquee<'static>,
+    value: Marquee<'static>,
+    /// The settings and the difficulty of the selected game when the screen was opened, to only save
+    /// them when they were actually changed
+    opened_with: (Settings, Option<Difficulty>),
+}
+
+impl SettingsScreen {
+    fn new(display: &dyn PixelDisplay, opened_with: (Settings, Option<Difficulty>)) -> Self {
+        let (label, rest) = Rect::of(display).split_top(layout::line_height(&Tiny));
+        Self {
+            selected: 0,
+            opened_with,
+            label: Marquee::new("", label).font(&Tiny),
+            value: Marquee::new("", rest).font(&Monospace),
+        }
+    }
+
+    /// Updates the shown texts, restarting the marquees only when a text changed. Returns true if
+    /// any text changed.
+    fn show(&mut self, label: &'static str, value: &'static str) -> bool {
+        let changed = self.label.text() != label || self.value.text() != value;
+        if self.label.text() != label {
+            self.label.set_text(label);
+        }
+        if self.value.text() != value {
+            self.value.set_text(value);
+        }
+        changed
+    }
+
+    /// Scrolls the texts, returns true if they moved
+    fn animate(&mut self, elapsed: Duration) -> bool {
+        self.label.update(elapsed) | self.value.update(elapsed)
+    }
+
+    fn draw(&self, display: &mut dyn PixelDisplay) {
+        display.clear();
+        self.label.draw(display);
+        self.value.draw(display);
+    }
+}
+
+/// Picks the next (`step` 1) or previous (`step` -1) of the choices, wrapping around
+fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, step: isize) -> T {
+    let index = choices.iter().position(|c| *c == current).unwrap_or(0) as isize;
+    choices[(index + step).rem_euclid(choices.len() as isize) as usize]
+}
+
+/// Time each game is shown once the selection screen starts cycling, see [`Settings::attract_timeout`]
+const ATTRACT_INTERVAL: Duration = Duration::from_secs(3);
+
+/// What the menu shows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Screen {
+    Game,
+    Selection,
+    Scores,
+    Settings,
+    Pause,
+}
+
+/// Duration of the slide animation when switching between games
+const TRANSITION_TIME: Duration = Duration::from_millis(250);
+
+/// A slide from one game of the selection screen to the current one
+#[derive(Clone, Copy)]
+struct Transition {
+    from: usize,
+    /// 1 when the new game slides in from the right, -1 when it slides in from the left
+    direction: isize,
+    time: Duration,
+}
+
+impl Transition {
+    /// Distance in columns the pages have moved so far
+    fn progress(&self, width: usize) -> isize {
+        let progress = self.time.as_micros() * width as u128 / TRANSITION_TIME.as_micros();
+        progress.min(width as u128) as isize
+    }
+}
+
+/// Areas of the selection screen: the icon, the name below it and the row of position dots at the bottom
+fn selection_layout(display: &dyn PixelDisplay) -> (Rect, Rect, usize) {
+    let rows = display.rows();
+    let name_height = layout::line_height(&Tiny);
+    let (icon, rest) =
+        Rect::of(display).split_top(rows.saturating_sub(name_height + LINE_SPACING + 1));
+    let (_, rest) = rest.split_top(LINE_SPACING);
+    let (name, _) = rest.split_top(Tiny.height());
+
+    (icon, name, rows.saturating_sub(1))
+}
+
+/// Lists the high scores starting at the entry `scroll`. Narrow displays show the score on the line
+/// below the initials.
+fn draw_scores(display: &mut dyn PixelDisplay, table: &HighScores, scroll: usize) {
+    display.clear();
+
+    let mut area = Rect::of(display);
+    if table.entries().is_empty() {
+        let row = (area.height.saturating_sub(Tiny.height()) / 2) as isize;
+        let line = Rect::new(row, 0, Tiny.height(), area.width);
+        layout::draw_aligned(display, &Tiny, line, Align::Center, "NONE");
+        return;
+    }
+
+    let initials_width = layout::text_width(&Tiny, "WWW") + 1;
+    let stacked = initials_width + layout::text_width(&Tiny, "999K") > area.width;
+    let score_width = if stacked {
+        area.width
+    } else {
+        area.width - initials_width
+    };
+
+    for entry in table.entries().iter().skip(scroll) {
+        let lines = if stacked { 2 } else { 1 };
+        if area.height < layout::line_height(&Tiny) * lines - LINE_SPACING {
+            break;
+        }
+
+        let mut score = Number::unsigned(entry.score);
+        if layout::text_width(&Tiny, score.as_str()) > score_width {
+            score = Number::compact(entry.score);
+        }
+
+        let (line, rest) = area.split_top(layout::line_height(&Tiny));
+        layout::draw_aligned(display, &Tiny, line, Align::Left, entry.initials());
+        let (line, rest) = if stacked {
+            rest.split_top(layout::line_height(&Tiny))
+        } else {
+            (line, rest)
+        };
+        layout::draw_aligned(display, &Tiny, line, Align::Right, score.as_str());
+        area = rest;
+    }
+}
+
+pub struct GameMenu<'a, I: Input, R: RandomNumberSource> {
+    games: &'a mut [&'a mut dyn Game<I, R>],
     current_index: usize,
+    /// True while the selection screen is shown instead of the current game
+    selecting: bool,
+    /// The scrolling name of the selected game, created on the first draw as it depends on the display size
+    name: Option<Marquee<'static>>,
+    transition: Option<Transition>,
+    /// Index of the first entry shown while browsing the high scores of the selected game
+    scores: Option<usize>,
+    /// Set while the current game is paused, the game is not updated during that time
+    pause: Option<PauseOverlay>,
+    settings: Settings,
+    /// Set while the settings screen is shown
+    settings_screen: Option<SettingsScreen>,
+    /// True when the settings changed since they were loaded or saved
+    settings_dirty: bool,
+    /// Whether the record written by [`GameMenu::save_game`] has to be removed again
+    saved_round: SavedRound,
+    /// Time since the last input on the selection screen, for the attract mode
+    idle: Duration,
+    /// True when the screens of the menu changed since they were last rendered
+    dirty: bool,
+    /// The events of the current game during the last update
+    events: GameEventBuffer,
 }
 
-impl<'a, I: Input, D: PixelDisplay, R: RandomNumberSource> GameMenu<'a, I, D, R> {
-    pub fn new(games: &'a mut [&'a mut dyn Game<I, D, R>]) -> Self {
+impl<'a, I: Input, R: RandomNumberSource> GameMenu<'a, I, R> {
+    pub fn new(games: &'a mut [&'a mut dyn Game<I, R>]) -> Self {
+        games[0].on_enter();
+
         Self {
             games,
             current_index: 0,
+            selecting: true,
+            name: None,
+            transition: None,
+            scores: None,
+            pause: None,
+            settings: Settings::new(),
+            settings_screen: None,
+            settings_dirty: false,
+            saved_round: SavedRound::None,
+            idle: Duration::ZERO,
+            dirty: true,
+            events: GameEventBuffer::new(),
         }
     }
-}
 
-impl<I: Input, D: PixelDisplay, R: RandomNumberSource> Game<I, D, R> for GameMenu<'_, I, D, R> {
-    fn update(&mut self, elapsed: Duration, input: &I, display: &mut D, random: &mut R) {
-        // delegate to the currently selected game
-        self.games[self.current_index].update(elapsed, input, display, random);
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Loads the system settings and the difficulty of each game, keeping the current ones for
+    /// anything not stored
+    pub fn load_settings<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+    ) -> Result<(), StorageError> {
+        self.settings.load(storage)?;
+        for game in self.games.iter_mut() {
+            if game.difficulty().is_some()
+                && let Some(difficulty) = Difficulty::load(storage, game.name())?
+            {
+                game.set_difficulty(difficulty);
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the settings if they were changed on the settings screen, cheap to call every update
+    pub fn save_settings<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+    ) -> Result<(), StorageError> {
+        if !self.settings_dirty {
+            return Ok(());
+        }
+
+        // a failure is reported once instead of being retried every update
+        self.settings_dirty = false;
+        self.settings.save(storage)?;
+        for game in self.games.iter() {
+            if let Some(difficulty) = game.difficulty() {
+                difficulty.save(storage, game.name())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the high scores of all games, games without stored scores keep their current ones
+    pub fn load_high_scores<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+    ) -> Result<(), StorageError> {
+        for game in self.games.iter_mut() {
+            let name = game.name();
+            if let Some(scores) = game.high_scores_mut() {
+                scores.load(storage, name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the high scores that changed since they were loaded or saved, cheap to call every update
+    pub fn save_high_scores<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+    ) -> Result<(), StorageError> {
+        for game in self.games.iter_mut() {
+            let name = game.name();
+            if let Some(scores) = game.high_scores_mut()
+                && scores.is_dirty()
+            {
+                scores.save(storage, name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stores the round in progress, if any, so it can be continued using
+    /// [`GameMenu::resume_saved_game`]. Returns false if there was nothing to save.
+    pub fn save_game<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+    ) -> Result<bool, StorageError> {
+        let mut buffer = [0; MAX_SAVE_SIZE];
+        let mut writer = Writer::new(&mut buffer);
+        if !Game::save(self, &mut writer)? {
+            return Ok(false);
+        }
+
+        let len = writer.len();
+        storage.write(SAVE_NAMESPACE, SAVE_NAME, &buffer[..len])?;
+        self.saved_round = SavedRound::Current;
+        Ok(true)
+    }
+
+    /// Removes the round stored by [`GameMenu::save_game`] once it was resumed, restarted, quit or
+    /// lost, so it does not come back on the next start. Cheap to call every update.
+    pub fn discard_saved_game<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+    ) -> Result<(), StorageError> {
+        if self.saved_round != SavedRound::Outdated {
+            return Ok(());
+        }
+
+        // a failure is reported once instead of being retried every update
+        self.saved_round = SavedRound::None;
+        storage.write(SAVE_NAMESPACE, SAVE_NAME, &[])
+    }
+
+    /// Marks the saved round, if any, as no longer the one being played
+    fn outdate_saved_round(&mut self) {
+        if self.saved_round == SavedRound::Current {
+            self.saved_round = SavedRound::Outdated;
+        }
+    }
+
+    /// Continues the round stored by [`GameMenu::save_game`], returns false if none was stored. The
+    /// stored round is removed, so it is only continued once.
+    pub fn resume_saved_game<S: Storage + ?Sized>(
+        &mut self,
+        storage: &mut S,
+    ) -> Result<bool, StorageError> {
+        let mut buffer = [0; MAX_SAVE_SIZE];
+        let len = match storage.read(SAVE_NAMESPACE, SAVE_NAME, &mut buffer)? {
+            Some(len) if len > 0 => len,
+            _ => return Ok(false),
+        };
+
+        // remove the record first, a snapshot that cannot be restored should not be tried again
+        storage.write(SAVE_NAMESPACE, SAVE_NAME, &[])?;
+        self.saved_round = SavedRound::None;
+        Game::restore(self, &mut Reader::new(&buffer[..len]))?;
+        Ok(true)
+    }
+
+    /// Switches to another game, notifying both the current and the new game
+    fn select(&mut self, index: usize) {
+        if index != self.current_index {
+            self.games[self.current_index].on_exit();
+            self.current_index = index;
+            self.games[self.current_index].on_enter();
+            self.name = None;
+        }
+    }
+
+    /// Selects the next (`direction` 1) or previous (`direction` -1) game and slides it in
+    fn switch(&mut self, direction: isize) {
+        let count = self.games.len() as isize;
+        let index = (self.current_index as isize + direction).rem_euclid(count) as usize;
+        if index != self.current_index {
+            self.transition = Some(Transition {
+                from: self.current_index,
+                direction,
+                time: Duration::ZERO,
+            });
+            self.select(index);
+        }
+    }
 
-        // when the game is in the start state, allow changing the game using left & right
-        if self.games[self.current_index].state() == GameState::Start {
-            if input.left() {
-                self.current_index = if self.current_index == 0 {
-                    self.games.len() - 1
+    fn screen(&self) -> Screen {
+        if self.pause.is_some() {
+            Screen::Pause
+        } else if self.scores.is_some() {
+            Screen::Scores
+        } else if self.settings_screen.is_some() {
+            Screen::Settings
+        } else if self.selecting {
+            Screen::Selection
+        } else {
+            Screen::Game
+        }
+    }
+
+    pub fn is_selecting(&self) -> bool {
+        self.selecting
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_some()
+    }
+
+    /// Shows the pause screen and freezes the current game, does nothing unless a game is being
+    /// played. Frontends with a dedicated pause key call this, others rely on [`PAUSE_GESTURE`].
+    pub fn pause(&mut self, display: &dyn PixelDisplay) {
+        if self.screen() == Screen::Game
+            && self.games[self.current_index].state() == GameState::Playing
+        {
+            self.games[self.current_index].on_pause();
+            self.pause = Some(PauseOverlay::new(display));
+            self.dirty = true;
+        }
+    }
+
+    /// Hides the pause screen and lets the current game continue where it was paused
+    pub fn resume(&mut self) {
+        if self.pause.take().is_some() {
+            self.outdate_saved_round();
+            self.games[self.current_index].on_resume();
+        }
+    }
+
+    fn update_selection(&mut self, elapsed: Duration, input: &I, display: &dyn PixelDisplay) {
+        self.idle += elapsed;
+        if input.any() {
+            self.idle = Duration::ZERO;
+        }
+
+        if input.left() {
+            self.switch(-1);
+        } else if input.right() {
+            self.switch(1);
+        } else if input.down() && self.games[self.current_index].high_scores().is_some() {
+            self.scores = Some(0);
+        } else if input.up() {
+            let item = self.settings_items()[0];
+            let opened_with = (self.settings, self.games[self.current_index].difficulty());
+            let mut screen = SettingsScreen::new(display, opened_with);
+            screen.show(item.label(), self.setting_value(item));
+            self.settings_screen = Some(screen);
+            return;
+        } else if input.action() {
+            // leave the (possibly still running) transition behind and start the game
+            self.transition = None;
+            self.selecting = false;
+            return;
+        }
+
+        // attract mode: show off the games one after the other while nobody is playing
+        if let Some(timeout) = self.settings.attract_timeout
+            && self.idle >= timeout + ATTRACT_INTERVAL
+        {
+            self.idle = timeout;
+            self.switch(1);
+        }
+
+        if let Some(transition) = self.transition.as_mut() {
+            transition.time += elapsed;
+            if transition.time >= TRANSITION_TIME {
+                self.transition = None;
+            }
+            self.dirty = true;
+        }
+
+        let (_, name_area, _) = selection_layout(display);
+        let name = self.games[self.current_index].name();
+        let name = self.name.get_or_insert_with(|| {
+            Marquee::new(name, name_area)
+                .font(&Tiny)
+                .mode(ScrollMode::Bounce)
+        });
+        self.dirty |= name.update(elapsed);
+    }
+
+    fn draw_selection(&self, display: &mut dyn PixelDisplay) {
+        display.clear();
+
+        let width = display.columns();
+        match self.transition {
+            Some(transition) => {
+                let progress = transition.progress(width);
+                let direction = transition.direction;
+                self.draw_page(display, transition.from, -direction * progress);
+                self.draw_page(
+                    display,
+                    self.current_index,
+                    direction * (width as isize - progress),
+                );
+            }
+            None => self.draw_page(display, self.current_index, 0),
+        }
+
+        self.draw_position(display);
+    }
+
+    /// Draws the icon and name of a game, moved sideways by `shift` columns
+    fn draw_page(&self, display: &mut dyn PixelDisplay, index: usize, shift: isize) {
+        let (icon_area, name_area, _) = selection_layout(display);
+        let game = &self.games[index];
+
+        let icon = game.icon();
+        let row = icon_area.row + (icon_area.height.saturating_sub(icon.height()) / 2) as isize;
+        let col = icon_area.col + (icon_area.width as isize - icon.width as isize) / 2;
+        display.draw_glyph(row, col + shift, &icon.glyph());
+
+        match &self.name {
+            Some(name) if shift == 0 && index == self.current_index => name.draw(display),
+            _ => {
+                // a name that does not fit is shown from its start, just like the marquee begins
+                let name = game.name();
+                let align = if layout::text_width(&Tiny, name) <= name_area.width {
+                    Align::Center
                 } else {
-                    self.current_index - 1
+                    Align::Left
+                };
+                layout::draw_aligned(display, &Tiny, name_area.translate(0, shift), align, name);
+            }
+        }
+    }
+
+    /// The options shown for the selected game
+    fn settings_items(&self) -> &'static [SettingsItem] {
+        if self.games[self.current_index].difficulty().is_some() {
+            &SettingsItem::ALL
+        } else {
+            &SettingsItem::ALL[1..]
+        }
+    }
+
+    fn setting_value(&self, item: SettingsItem) -> &'static str {
+        match item {
+            SettingsItem::Difficulty => self.games[self.current_index]
+                .difficulty()
+                .map_or("-", |difficulty| difficulty.label()),
+            SettingsItem::Refresh => self.settings.refresh.label(),
+            SettingsItem::Attract => self.settings.attract_label(),
+            SettingsItem::Sound if self.settings.sound => "ON",
+            SettingsItem::Sound => "OFF",
+        }
+    }
+
+    /// Changes an option to its next (`step` 1) or previous (`step` -1) value
+    fn change_setting(&mut self, item: SettingsItem, step: isize) {
+        match item {
+            SettingsItem::Difficulty => {
+                let game = &mut self.games[self.current_index];
+                if let Some(difficulty) = game.difficulty() {
+                    game.set_difficulty(cycle(&Difficulty::ALL, difficulty, step));
                 }
             }
+            SettingsItem::Refresh => {
+                self.settings.refresh = cycle(&RefreshMode::ALL, self.settings.refresh, step);
+            }
+            SettingsItem::Attract => {
+                let timeouts = ATTRACT_TIMEOUTS.map(|(timeout, _)| timeout);
+                self.settings.attract_timeout =
+                    cycle(&timeouts, self.settings.attract_timeout, step);
+            }
+            SettingsItem::Sound => self.settings.sound = !self.settings.sound,
+        }
+    }
+
+    /// Goes through the options using up & down and changes them using left & right, the action
+    /// button goes back to the selection
+    fn update_settings(&mut self, elapsed: Duration, input: &I) {
+        let items = self.settings_items();
+        let Some(screen) = self.settings_screen.as_mut() else {
+            return;
+        };
+
+        if input.action() {
+            let current = (self.settings, self.games[self.current_index].difficulty());
+            self.settings_dirty |= screen.opened_with != current;
+            self.settings_screen = None;
+            self.idle = Duration::ZERO;
+            return;
+        }
+
+        let count = items.len();
+        if input.up() {
+            screen.selected = (screen.selected + count - 1) % count;
+        }
+        if input.down() {
+            screen.selected = (screen.selected + 1) % count;
+        }
+        let item = items[screen.selected.min(count - 1)];
+
+        if input.left() {
+            self.change_setting(item, -1);
+        } else if input.right() {
+            self.change_setting(item, 1);
+        }
+
+        let value = self.setting_value(item);
+        if let Some(screen) = self.settings_screen.as_mut() {
+            self.dirty |= screen.show(item.label(), value);
+            self.dirty |= screen.animate(elapsed);
+        }
+    }
+
+    /// Scrolls through the high scores using up & down, any other button goes back to the selection
+    fn update_scores(&mut self, input: &I) {
+        let Some(table) = self.games[self.current_index].high_scores() else {
+            self.scores = None;
+            return;
+        };
+        let mut scroll = self.scores.unwrap_or(0);
+
+        if input.down() {
+            scroll = (scroll + 1).min(table.entries().len().saturating_sub(1));
+        }
+        if (input.up() && scroll == 0) || input.left() || input.right() || input.action() {
+            self.scores = None;
+            return;
+        }
+        if input.up() {
+            scroll -= 1;
+        }
+
+        self.scores = Some(scroll);
+    }
+
+    /// Draws a dot for each game on the bottom row, with a dash for the selected one
+    fn draw_position(&self, display: &mut dyn PixelDisplay) {
+        let (_, _, row) = selection_layout(display);
+        let dash = 3;
+        let total = (self.games.len() - 1) * 2 + dash;
+        let mut col = display.columns().saturating_sub(total) / 2;
+
+        for index in 0..self.games.len() {
+            let width = if index == self.current_index { dash } else { 1 };
+            for c in col..col + width {
+                display.set_pixel(row, c, Pixel::On);
+            }
+            col += width + 1;
+        }
+    }
+}
+
+impl<I: Input, R: RandomNumberSource> Game<I, R> for GameMenu<'_, I, R> {
+    fn update(&mut self, elapsed: Duration, input: &I, display: &dyn PixelDisplay, random: &mut R) {
+        self.events.clear();
+        let screen = self.screen();
+        if screen != Screen::Game && input.any() {
+            self.dirty = true;
+        }
 
-            if input.right() {
-                self.current_index = if self.current_index == self.games.len() - 1 {
-                    0
+        match screen {
+            Screen::Pause => {
+                let Some(pause) = self.pause.as_mut() else {
+                    return;
+                };
+                self.dirty |= pause.animate(elapsed);
+                match pause.update(input) {
+                    Some(PauseOption::Resume) => self.resume(),
+                    Some(PauseOption::Restart) => {
+                        self.games[self.current_index].reset();
+                        self.pause = None;
+                        self.outdate_saved_round();
+                    }
+                    Some(PauseOption::Quit) => self.reset(),
+                    None => {}
+                }
+            }
+            Screen::Scores => self.update_scores(input),
+            Screen::Settings => self.update_settings(elapsed, input),
+            Screen::Selection => self.update_selection(elapsed, input, display),
+            Screen::Game => {
+                if self.games[self.current_index].state() == GameState::Playing
+                    && input.gestures().contains(&PAUSE_GESTURE)
+                {
+                    self.pause(display);
                 } else {
-                    self.current_index + 1
+                    // delegate to the currently selected game
+                    self.games[self.current_index].update(elapsed, input, display, random);
+                    self.events.extend(self.games[self.current_index].events());
+                    if self.games[self.current_index].state() == GameState::GameOver {
+                        self.outdate_saved_round();
+                    }
+
+                    // left & right on the ready screen of a game go back to the selection, as
+                    // seen on that screen, i.e. turned with the game
+                    let game = &self.games[self.current_index];
+                    let input = OrientedInput::new(input, game.orientation());
+                    if game.state() == GameState::Start && (input.left() || input.right()) {
+                        self.selecting = true;
+                    }
                 }
             }
+        }
+
+        // switching to another screen always needs a redraw
+        self.dirty |= self.screen() != screen;
+    }
+
+    fn render(&mut self, display: &mut dyn PixelDisplay) {
+        match self.screen() {
+            Screen::Pause => {
+                if let Some(pause) = self.pause.as_ref() {
+                    pause.draw(display);
+                }
+            }
+            Screen::Scores => {
+                if let Some(table) = self.games[self.current_index].high_scores() {
+                    draw_scores(display, table, self.scores.unwrap_or(0));
+                }
+            }
+            Screen::Settings => {
+                if let Some(screen) = self.settings_screen.as_ref() {
+                    screen.draw(display);
+                }
+            }
+            Screen::Selection => self.draw_selection(display),
+            Screen::Game => self.games[self.current_index].render(display),
+        }
+        self.dirty = false;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty || (self.screen() == Screen::Game && self.games[self.current_index].is_dirty())
+    }
+
+    fn events(&self) -> &[GameEvent] {
+        self.events.as_slice()
+    }
+
+    fn state(&self) -> GameState {
+        if self.selecting {
+            GameState::Start
+        } else {
+            self.games[self.current_index].state()
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.games[self.current_index].name()
+    }
+
+    fn icon(&self) -> Icon {
+        self.games[self.current_index].icon()
+    }
+
+    fn score(&self) -> Option<usize> {
+        self.games[self.current_index].score()
+    }
+
+    fn high_scores(&self) -> Option<&HighScores> {
+        self.games[self.current_index].high_scores()
+    }
+
+    fn high_scores_mut(&mut self) -> Option<&mut HighScores> {
+        self.games[self.current_index].high_scores_mut()
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        self.games[self.current_index].difficulty()
+    }
+
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.games[self.current_index].set_difficulty(difficulty);
+    }
+
+    /// Writes the name of the current game followed by its snapshot
+    fn save(&self, writer: &mut Writer) -> Result<bool, SnapshotError> {
+        if self.selecting {
+            return Ok(false);
+        }
+
+        let name = self.games[self.current_index].name();
+        // remember the position, so the name can be dropped again when the game has nothing to save
+        let start = writer.len();
+        writer.u8(name.len() as u8)?;
+        writer.bytes(name.as_bytes())?;
+        let saved = self.games[self.current_index].save(writer)?;
+        if !saved {
+            writer.truncate(start);
+        }
+        Ok(saved)
+    }
+
+    /// Selects the game named in the snapshot and continues its round
+    fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+        let len = reader.u8()? as usize;
+        let name = reader.bytes(len)?;
+        let index = self
+            .games
+            .iter()
+            .position(|game| game.name().as_bytes() == name)
+            .ok_or(SnapshotError::Invalid)?;
+
+        self.select(index);
+        self.games[index].restore(reader)?;
+        self.pause = None;
+        self.scores = None;
+        self.transition = None;
+        self.selecting = false;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Abandons the current game (also when it is paused) and goes back to selecting a game
+    fn reset(&mut self) {
+        self.games[self.current_index].reset();
+        self.outdate_saved_round();
+        self.pause = None;
+        self.scores = None;
+        self.settings_screen = None;
+        self.selecting = true;
+        self.name = None;
+        self.dirty = true;
+    }
+
+    fn on_enter(&mut self) {
+        self.games[self.current_index].on_enter();
+    }
+
+    fn on_exit(&mut self) {
+        self.games[self.current_index].on_exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{
+        framebuffer::Framebuffer,
+        input::{Controller, InputEvent, InputEventKind},
+        orientation::{Orientation, OrientedGame, Rotation},
+        random::Pcg32,
+        storage::tests::MemoryStorage,
+    };
+
+    const TICK: Duration = Duration::from_millis(10);
+
+    /// Started by the action button, then counts its presses until left ends the round
+    struct Counter {
+        state: GameState,
+        presses: u8,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            Self {
+                state: GameState::Start,
+                presses: 0,
+            }
+        }
+    }
+
+    impl<I: Input, R: RandomNumberSource> Game<I, R> for Counter {
+        fn update(&mut self, _: Duration, input: &I, _: &dyn PixelDisplay, _: &mut R) {
+            match self.state {
+                GameState::Start if input.action() => self.state = GameState::Playing,
+                GameState::Playing if input.left() => self.state = GameState::GameOver,
+                GameState::Playing if input.action() => self.presses += 1,
+                _ => {}
+            }
+        }
+
+        fn render(&mut self, _display: &mut dyn PixelDisplay) {}
+
+        fn state(&self) -> GameState {
+            self.state
+        }
+
+        fn name(&self) -> &'static str {
+            "COUNTER"
+        }
+
+        fn icon(&self) -> Icon {
+            Icon::new(&[0xFF], 8)
+        }
+
+        fn score(&self) -> Option<usize> {
+            Some(self.presses as usize)
+        }
+
+        fn save(&self, writer: &mut Writer) -> Result<bool, SnapshotError> {
+            if self.state != GameState::Playing {
+                return Ok(false);
+            }
+            writer.u8(self.presses)?;
+            Ok(true)
+        }
 
-            // draw some "arrows" in the bottom corners
-            display.set_pixel(display.rows() - 1, 1, crate::display::Pixel::On);
-            display.set_pixel(display.rows() - 2, 0, crate::display::Pixel::On);
-            display.set_pixel(display.rows() - 3, 1, crate::display::Pixel::On);
-            display.set_pixel(
-                display.rows() - 1,
-                display.columns() - 2,
-                crate::display::Pixel::On,
-            );
-            display.set_pixel(
-                display.rows() - 2,
-                display.columns() - 1,
-                crate::display::Pixel::On,
-            );
-            display.set_pixel(
-                display.rows() - 3,
-                display.columns() - 2,
-                crate::display::Pixel::On,
-            );
+        fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+            self.presses = reader.u8()?;
+            self.state = GameState::Playing;
+            Ok(())
         }
+
+        fn reset(&mut self) {
+            *self = Self::new();
+        }
+    }
+
+    type Menu<'a> = GameMenu<'a, Controller, Pcg32>;
+
+    /// Runs a single update of the menu with the given button changes
+    fn tick(menu: &mut Menu, controller: &mut Controller, changes: &[(Button, InputEventKind)]) {
+        let events: Vec<_> = changes
+            .iter()
+            .map(|&(button, kind)| InputEvent {
+                button,
+                kind,
+                timestamp: Duration::ZERO,
+            })
+            .collect();
+        controller.update(&events, TICK);
+        menu.update(
+            TICK,
+            controller,
+            &Framebuffer::<16, 16>::new(),
+            &mut Pcg32::new(1),
+        );
+    }
+
+    fn tap(menu: &mut Menu, controller: &mut Controller, button: Button) {
+        tick(menu, controller, &[(button, InputEventKind::Pressed)]);
+        tick(menu, controller, &[(button, InputEventKind::Released)]);
+        // the release is only seen with the following update
+        tick(menu, controller, &[]);
+    }
+
+    /// Picks the game on the selection screen and starts its round
+    fn play(menu: &mut Menu, controller: &mut Controller) {
+        tap(menu, controller, Button::Action);
+        tap(menu, controller, Button::Action);
+        assert_eq!(menu.state(), GameState::Playing);
+    }
+
+    #[test]
+    fn action_reaches_the_game_when_pressed() {
+        let mut game = Counter::new();
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::new(&mut games);
+        let mut controller = Controller::default();
+        play(&mut menu, &mut controller);
+
+        tick(
+            &mut menu,
+            &mut controller,
+            &[(Button::Action, InputEventKind::Pressed)],
+        );
+        assert_eq!(menu.score(), Some(1));
+
+        // holding it does not pause the game
+        for _ in 0..100 {
+            tick(&mut menu, &mut controller, &[]);
+        }
+        assert!(!menu.is_paused());
+        assert_eq!(menu.score(), Some(1));
+    }
+
+    #[test]
+    fn up_and_down_together_pause_the_game() {
+        let mut game = Counter::new();
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::new(&mut games);
+        let mut controller = Controller::default();
+        play(&mut menu, &mut controller);
+
+        tick(
+            &mut menu,
+            &mut controller,
+            &[
+                (Button::Up, InputEventKind::Pressed),
+                (Button::Down, InputEventKind::Pressed),
+            ],
+        );
+        for _ in 0..10 {
+            tick(&mut menu, &mut controller, &[]);
+        }
+        assert!(menu.is_paused());
+    }
+
+    #[test]
+    fn pause_only_pauses_a_running_game() {
+        let mut game = Counter::new();
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::new(&mut games);
+        let mut controller = Controller::default();
+        let display = Framebuffer::<16, 16>::new();
+
+        menu.pause(&display);
+        assert!(!menu.is_paused());
+
+        play(&mut menu, &mut controller);
+        menu.pause(&display);
+        assert!(menu.is_paused());
+        assert!(Game::<Controller, Pcg32>::is_dirty(&menu));
+    }
+
+    /// Plays a round, pauses and saves it, then lets `after` continue with the paused menu
+    fn save_paused_round(
+        storage: &mut MemoryStorage,
+        after: impl FnOnce(&mut Menu, &mut Controller),
+    ) {
+        let mut game = Counter::new();
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::new(&mut games);
+        let mut controller = Controller::default();
+        play(&mut menu, &mut controller);
+        tap(&mut menu, &mut controller, Button::Action);
+
+        menu.pause(&Framebuffer::<16, 16>::new());
+        assert_eq!(menu.save_game(storage), Ok(true));
+
+        after(&mut menu, &mut controller);
+        menu.discard_saved_game(storage).unwrap();
+    }
+
+    /// Starts the menu again and tries to continue the saved round
+    fn resume_saved_round(storage: &mut MemoryStorage) -> Result<bool, StorageError> {
+        let mut game = Counter::new();
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::<Controller, Pcg32>::new(&mut games);
+        let resumed = menu.resume_saved_game(storage);
+        if resumed == Ok(true) {
+            assert_eq!(menu.score(), Some(1));
+        }
+        resumed
+    }
+
+    #[test]
+    fn paused_round_is_continued_on_the_next_start() {
+        let mut storage = MemoryStorage::default();
+        save_paused_round(&mut storage, |_, _| {});
+        assert_eq!(resume_saved_round(&mut storage), Ok(true));
+        // only once
+        assert_eq!(resume_saved_round(&mut storage), Ok(false));
+    }
+
+    #[test]
+    fn round_lost_after_resuming_is_not_continued() {
+        let mut storage = MemoryStorage::default();
+        save_paused_round(&mut storage, |menu, controller| {
+            menu.resume();
+            tap(menu, controller, Button::Left);
+            assert_eq!(menu.state(), GameState::GameOver);
+        });
+        assert_eq!(resume_saved_round(&mut storage), Ok(false));
+    }
+
+    #[test]
+    fn round_restarted_or_quit_from_the_pause_is_not_continued() {
+        for option in [PauseOption::Restart, PauseOption::Quit] {
+            let mut storage = MemoryStorage::default();
+            save_paused_round(&mut storage, |menu, controller| {
+                while menu.pause.as_ref().unwrap().selected() != option {
+                    tap(menu, controller, Button::Down);
+                }
+                tap(menu, controller, Button::Action);
+                assert!(!menu.is_paused());
+            });
+            assert_eq!(resume_saved_round(&mut storage), Ok(false), "{option:?}");
+        }
+    }
+
+    #[test]
+    fn settings_are_only_saved_when_changed() {
+        let mut game = Counter::new();
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::new(&mut games);
+        let mut controller = Controller::default();
+        let mut storage = MemoryStorage::default();
+
+        // opened and closed again
+        tap(&mut menu, &mut controller, Button::Up);
+        tap(&mut menu, &mut controller, Button::Action);
+        menu.save_settings(&mut storage).unwrap();
+        assert_eq!(storage.writes, 0);
+
+        // changed and changed back
+        tap(&mut menu, &mut controller, Button::Up);
+        tap(&mut menu, &mut controller, Button::Right);
+        tap(&mut menu, &mut controller, Button::Left);
+        tap(&mut menu, &mut controller, Button::Action);
+        menu.save_settings(&mut storage).unwrap();
+        assert_eq!(storage.writes, 0);
+
+        tap(&mut menu, &mut controller, Button::Up);
+        tap(&mut menu, &mut controller, Button::Right);
+        tap(&mut menu, &mut controller, Button::Action);
+        assert_eq!(menu.screen(), Screen::Selection);
+        menu.save_settings(&mut storage).unwrap();
+        assert_eq!(storage.writes, 1);
+        menu.save_settings(&mut storage).unwrap();
+        assert_eq!(storage.writes, 1);
+    }
+
+    #[test]
+    fn failed_settings_save_is_not_retried_every_update() {
+        let mut game = Counter::new();
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::new(&mut games);
+        let mut controller = Controller::default();
+        let mut storage = MemoryStorage::default();
+        storage.failing = true;
+
+        tap(&mut menu, &mut controller, Button::Up);
+        tap(&mut menu, &mut controller, Button::Right);
+        tap(&mut menu, &mut controller, Button::Action);
+        assert_eq!(menu.save_settings(&mut storage), Err(StorageError::Io));
+        assert_eq!(menu.save_settings(&mut storage), Ok(()));
+        assert_eq!(storage.writes, 1);
+    }
+
+    #[test]
+    fn ready_screen_of_a_turned_game_goes_back_in_its_orientation() {
+        let mut game = OrientedGame::new(Counter::new(), Orientation::new(Rotation::R90));
+        let mut games = [&mut game as &mut dyn Game<_, _>];
+        let mut menu = GameMenu::new(&mut games);
+        let mut controller = Controller::default();
+        tap(&mut menu, &mut controller, Button::Action);
+        assert_eq!(menu.screen(), Screen::Game);
+
+        // down and up as seen by the game
+        tap(&mut menu, &mut controller, Button::Left);
+        tap(&mut menu, &mut controller, Button::Right);
+        assert_eq!(menu.screen(), Screen::Game);
+
+        // left as seen by the game
+        tap(&mut menu, &mut controller, Button::Up);
+        assert_eq!(menu.screen(), Screen::Selection);
     }
 }
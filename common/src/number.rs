@@ -0,0 +1,200 @@
+use core::fmt;
+
+/// Large enough for the digits of any `usize`/`isize` including sign, and for reasonable zero padding
+const CAPACITY: usize = 32;
+
+/// Suffixes used for compacting large numbers, each one a factor 1000 larger than the previous
+const SUFFIXES: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+
+/// A number formatted into a fixed-size buffer on the stack, so it can be drawn without allocating
+#[derive(Clone, Copy)]
+pub struct Number {
+    buffer: [u8; CAPACITY],
+    start: usize,
+}
+
+impl Number {
+    fn empty() -> Self {
+        Self {
+            buffer: [b'0'; CAPACITY],
+            start: CAPACITY,
+        }
+    }
+
+    fn push_front(&mut self, byte: u8) {
+        if self.start > 0 {
+            self.start -= 1;
+            self.buffer[self.start] = byte;
+        }
+    }
+
+    fn push_digits(&mut self, mut value: usize) {
+        loop {
+            self.push_front(b'0' + (value % 10) as u8);
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+    }
+
+    pub fn unsigned(value: usize) -> Self {
+        let mut number = Self::empty();
+        number.push_digits(value);
+        number
+    }
+
+    pub fn signed(value: isize) -> Self {
+        let mut number = Self::unsigned(value.unsigned_abs());
+        if value < 0 {
+            number.push_front(b'-');
+        }
+        number
+    }
+
+    /// Pads the number with leading zeros to (at least) `width` digits, e.g. `7` with width 3 becomes `007`
+    pub fn zero_padded(value: usize, width: usize) -> Self {
+        let mut number = Self::unsigned(value);
+        while number.len() < width.min(CAPACITY) {
+            number.push_front(b'0');
+        }
+        number
+    }
+
+    /// Shortens large numbers to at most three significant digits followed by a suffix,
+    /// e.g. `1234` becomes `1.2K`, `12345` becomes `12K` and `1234567` becomes `1.2M`.
+    /// Digits are truncated rather than rounded so the shown value never exceeds the real one.
+    pub fn compact(value: usize) -> Self {
+        if value < 1000 {
+            return Self::unsigned(value);
+        }
+
+        // find the largest suffix where the remaining value is at least 1
+        let mut scaled = value;
+        let mut remainder = 0;
+        let mut suffix = 0;
+        while scaled >= 1000 * 1000 && suffix < SUFFIXES.len() - 1 {
+            scaled /= 1000;
+            suffix += 1;
+        }
+        if scaled >= 1000 {
+            remainder = scaled % 1000;
+            scaled /= 1000;
+        }
+
+        let mut number = Self::empty();
+        number.push_front(SUFFIXES[suffix] as u8);
+        if scaled < 10 {
+            // room for a single decimal
+            number.push_front(b'0' + (remainder / 100) as u8);
+            number.push_front(b'.');
+        }
+        number.push_digits(scaled);
+        number
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ever contains ASCII digits, signs, dots and suffixes
+        core::str::from_utf8(&self.buffer[self.start..]).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        CAPACITY - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact() {
+        let cases = [
+            (0, "0"),
+            (999, "999"),
+            (1000, "1.0K"),
+            (1099, "1.0K"),
+            (1100, "1.1K"),
+            (9999, "9.9K"),
+            (10_000, "10K"),
+            (99_999, "99K"),
+            (100_000, "100K"),
+            (999_999, "999K"),
+            (1_000_000, "1.0M"),
+            (1_234_567, "1.2M"),
+            (9_999_999, "9.9M"),
+            (10_000_000, "10M"),
+            (999_999_999, "999M"),
+            (1_000_000_000, "1.0G"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(Number::compact(value).as_str(), expected, "{value}");
+        }
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn compact_stops_at_the_largest_suffix() {
+        assert_eq!(Number::compact(999_999_999_999_999_999).as_str(), "999P");
+        assert_eq!(Number::compact(1_000_000_000_000_000_000).as_str(), "1.0E");
+        assert_eq!(Number::compact(usize::MAX).as_str(), "18E");
+    }
+
+    #[test]
+    fn zero_padded() {
+        let cases = [
+            (7, 3, "007"),
+            (0, 1, "0"),
+            (0, 0, "0"),
+            (42, 2, "42"),
+            (1234, 2, "1234"),
+            (5, 6, "000005"),
+        ];
+        for (value, width, expected) in cases {
+            assert_eq!(
+                Number::zero_padded(value, width).as_str(),
+                expected,
+                "{value} {width}"
+            );
+        }
+    }
+
+    #[test]
+    fn zero_padding_is_limited_to_the_capacity() {
+        let number = Number::zero_padded(1, 100);
+        assert_eq!(number.len(), CAPACITY);
+        assert!(number.as_str().ends_with("01"));
+    }
+
+    #[test]
+    fn signed_and_unsigned() {
+        assert_eq!(Number::unsigned(0).as_str(), "0");
+        assert_eq!(
+            Number::unsigned(usize::MAX).len(),
+            usize::MAX.ilog10() as usize + 1
+        );
+        assert_eq!(Number::signed(-42).as_str(), "-42");
+        assert_eq!(Number::signed(42).as_str(), "42");
+        assert_eq!(
+            Number::signed(isize::MIN).len(),
+            isize::MIN.unsigned_abs().ilog10() as usize + 2
+        );
+        assert!(!Number::signed(0).is_empty());
+    }
+}
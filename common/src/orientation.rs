@@ -0,0 +1,431 @@
+//! Shows a game rotated and/or mirrored, e.g. a game made for a portrait display on the landscape
+//! panel. The buttons are remapped to match, so the game plays right with the whole device (panel
+//! and controller) turned sideways.
+
+use core::time::Duration;
+
+use crate::{
+    Game, GameState, Icon, RandomNumberSource,
+    display::{Pixel, PixelDisplay},
+    events::GameEvent,
+    highscore::HighScores,
+    input::{Button, Input},
+    settings::Difficulty,
+    snapshot::{Reader, SnapshotError, Writer},
+};
+
+/// Clockwise rotation of the picture on the physical display
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    R0,
+    R90,
+    R180,
+    R270,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Orientation {
+    pub rotation: Rotation,
+    /// Flips the picture horizontally before rotating it
+    pub mirror: bool,
+}
+
+impl Orientation {
+    pub const fn new(rotation: Rotation) -> Self {
+        Self {
+            rotation,
+            mirror: false,
+        }
+    }
+
+    pub const fn mirrored(mut self) -> Self {
+        self.mirror = !self.mirror;
+        self
+    }
+
+    /// True if rows and columns are swapped
+    pub fn is_sideways(&self) -> bool {
+        matches!(self.rotation, Rotation::R90 | Rotation::R270)
+    }
+
+    /// The size (rows, columns) the game sees on a physical display of the given size
+    pub fn size(&self, rows: usize, columns: usize) -> (usize, usize) {
+        if self.is_sideways() {
+            (columns, rows)
+        } else {
+            (rows, columns)
+        }
+    }
+
+    /// Maps a position as seen by the game to the physical display, `rows` and `columns` being the
+    /// size seen by the game
+    pub fn map(&self, row: usize, col: usize, rows: usize, columns: usize) -> (usize, usize) {
+        let col = if self.mirror { columns - 1 - col } else { col };
+
+        match self.rotation {
+            Rotation::R0 => (row, col),
+            Rotation::R90 => (col, rows - 1 - row),
+            Rotation::R180 => (rows - 1 - row, columns - 1 - col),
+            Rotation::R270 => (columns - 1 - col, row),
+        }
+    }
+
+    /// The physical button pointing in the direction of `button` as seen by the game
+    pub fn map_button(&self, button: Button) -> Button {
+        use Button::*;
+
+        let button = match (self.mirror, button) {
+            (true, Left) => Right,
+            (true, Right) => Left,
+            (_, button) => button,
+        };
+
+        match (self.rotation, button) {
+            (_, Action) | (Rotation::R0, _) => button,
+            (Rotation::R90, Up) => Right,
+            (Rotation::R90, Right) => Down,
+            (Rotation::R90, Down) => Left,
+            (Rotation::R90, Left) => Up,
+            (Rotation::R180, Up) => Down,
+            (Rotation::R180, Right) => Left,
+            (Rotation::R180, Down) => Up,
+            (Rotation::R180, Left) => Right,
+            (Rotation::R270, Up) => Left,
+            (Rotation::R270, Right) => Up,
+            (Rotation::R270, Down) => Right,
+            (Rotation::R270, Left) => Down,
+        }
+    }
+}
+
+/// Wraps a display and draws on it in the given orientation
+pub struct OrientedDisplay<'a> {
+    display: &'a mut dyn PixelDisplay,
+    orientation: Orientation,
+}
+
+impl<'a> OrientedDisplay<'a> {
+    pub fn new(display: &'a mut dyn PixelDisplay, orientation: Orientation) -> Self {
+        Self {
+            display,
+            orientation,
+        }
+    }
+}
+
+impl PixelDisplay for OrientedDisplay<'_> {
+    fn rows(&self) -> usize {
+        self.orientation
+            .size(self.display.rows(), self.display.columns())
+            .0
+    }
+
+    fn columns(&self) -> usize {
+        self.orientation
+            .size(self.display.rows(), self.display.columns())
+            .1
+    }
+
+    fn set_pixel(&mut self, row: usize, col: usize, value: Pixel) {
+        if row >= self.rows() || col >= self.columns() {
+            return;
+        }
+
+        let (row, col) = self.orientation.map(row, col, self.rows(), self.columns());
+        self.display.set_pixel(row, col, value);
+    }
+
+    fn fill(&mut self, value: Pixel) {
+        self.display.fill(value);
+    }
+}
+
+/// Wraps an input and remaps the directions to the given orientation. The events and gestures are
+/// not passed on, as they refer to the physical buttons.
+pub struct OrientedInput<'a, I: Input> {
+    input: &'a I,
+    orientation: Orientation,
+}
+
+impl<'a, I: Input> OrientedInput<'a, I> {
+    pub fn new(input: &'a I, orientation: Orientation) -> Self {
+        Self { input, orientation }
+    }
+}
+
+impl<I: Input> Input for OrientedInput<'_, I> {
+    fn left(&self) -> bool {
+        self.input.button(self.orientation.map_button(Button::Left))
+    }
+
+    fn right(&self) -> bool {
+        self.input
+            .button(self.orientation.map_button(Button::Right))
+    }
+
+    fn up(&self) -> bool {
+        self.input.button(self.orientation.map_button(Button::Up))
+    }
+
+    fn down(&self) -> bool {
+        self.input.button(self.orientation.map_button(Button::Down))
+    }
+
+    fn action(&self) -> bool {
+        self.input.action()
+    }
+}
+
+/// Only the size of a display, for [`Game::update`] which does not draw
+struct Size {
+    rows: usize,
+    columns: usize,
+}
+
+impl PixelDisplay for Size {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn columns(&self) -> usize {
+        self.columns
+    }
+
+    fn set_pixel(&mut self, _row: usize, _col: usize, _value: Pixel) {}
+}
+
+/// Runs a game in the given orientation, both its display and its input
+pub struct OrientedGame<G> {
+    game: G,
+    orientation: Orientation,
+}
+
+impl<G> OrientedGame<G> {
+    pub fn new(game: G, orientation: Orientation) -> Self {
+        Self { game, orientation }
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+}
+
+impl<G, I: Input, R: RandomNumberSource> Game<I, R> for OrientedGame<G>
+where
+    G: for<'a> Game<OrientedInput<'a, I>, R>,
+{
+    fn update(&mut self, elapsed: Duration, input: &I, display: &dyn PixelDisplay, random: &mut R) {
+        let (rows, columns) = self.orientation.size(display.rows(), display.columns());
+        let input = OrientedInput::new(input, self.orientation);
+        self.game
+            .update(elapsed, &input, &Size { rows, columns }, random);
+    }
+
+    fn render(&mut self, display: &mut dyn PixelDisplay) {
+        self.game
+            .render(&mut OrientedDisplay::new(display, self.orientation));
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.game.is_dirty()
+    }
+
+    fn events(&self) -> &[GameEvent] {
+        self.game.events()
+    }
+
+    fn state(&self) -> GameState {
+        self.game.state()
+    }
+
+    fn name(&self) -> &'static str {
+        self.game.name()
+    }
+
+    fn icon(&self) -> Icon {
+        self.game.icon()
+    }
+
+    fn score(&self) -> Option<usize> {
+        self.game.score()
+    }
+
+    fn high_scores(&self) -> Option<&HighScores> {
+        self.game.high_scores()
+    }
+
+    fn high_scores_mut(&mut self) -> Option<&mut HighScores> {
+        self.game.high_scores_mut()
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        self.game.difficulty()
+    }
+
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.game.set_difficulty(difficulty);
+    }
+
+    fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    fn save(&self, writer: &mut Writer) -> Result<bool, SnapshotError> {
+        self.game.save(writer)
+    }
+
+    fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+        self.game.restore(reader)
+    }
+
+    fn reset(&mut self) {
+        self.game.reset();
+    }
+
+    fn on_enter(&mut self) {
+        self.game.on_enter();
+    }
+
+    fn on_exit(&mut self) {
+        self.game.on_exit();
+    }
+
+    fn on_pause(&mut self) {
+        self.game.on_pause();
+    }
+
+    fn on_resume(&mut self) {
+        self.game.on_resume();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+
+    fn all() -> impl Iterator<Item = Orientation> {
+        [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270]
+            .into_iter()
+            .flat_map(|rotation| {
+                let orientation = Orientation::new(rotation);
+                [orientation, orientation.mirrored()]
+            })
+    }
+
+    /// The change of (row, column) when moving one step in the direction of `button`
+    fn step(button: Button) -> (isize, isize) {
+        match button {
+            Button::Up => (-1, 0),
+            Button::Down => (1, 0),
+            Button::Left => (0, -1),
+            Button::Right => (0, 1),
+            Button::Action => (0, 0),
+        }
+    }
+
+    #[test]
+    fn size_swaps_rows_and_columns_when_sideways() {
+        for orientation in all() {
+            let expected = if orientation.is_sideways() {
+                (5, 3)
+            } else {
+                (3, 5)
+            };
+            assert_eq!(orientation.size(3, 5), expected, "{orientation:?}");
+        }
+    }
+
+    #[test]
+    fn map_covers_the_physical_display_once() {
+        let (physical_rows, physical_columns) = (3, 5);
+        for orientation in all() {
+            let (rows, columns) = orientation.size(physical_rows, physical_columns);
+
+            let mut mapped = Vec::new();
+            for row in 0..rows {
+                for col in 0..columns {
+                    let (r, c) = orientation.map(row, col, rows, columns);
+                    assert!(
+                        r < physical_rows && c < physical_columns,
+                        "{orientation:?} maps {row},{col} to {r},{c}"
+                    );
+                    mapped.push((r, c));
+                }
+            }
+
+            mapped.sort();
+            mapped.dedup();
+            assert_eq!(mapped.len(), rows * columns, "{orientation:?}");
+        }
+    }
+
+    #[test]
+    fn map_rotates_clockwise() {
+        // the top left corner of a 3x5 picture
+        let corner = |orientation: Orientation| {
+            let (rows, columns) = orientation.size(3, 5);
+            orientation.map(0, 0, rows, columns)
+        };
+        assert_eq!(corner(Orientation::new(Rotation::R0)), (0, 0));
+        assert_eq!(corner(Orientation::new(Rotation::R90)), (0, 4));
+        assert_eq!(corner(Orientation::new(Rotation::R180)), (2, 4));
+        assert_eq!(corner(Orientation::new(Rotation::R270)), (2, 0));
+        assert_eq!(corner(Orientation::new(Rotation::R0).mirrored()), (0, 4));
+        assert_eq!(corner(Orientation::new(Rotation::R90).mirrored()), (2, 4));
+    }
+
+    #[test]
+    fn map_button_points_where_map_moves() {
+        for orientation in all() {
+            let (rows, columns) = orientation.size(5, 7);
+            let (row, col) = (2, 3);
+            let (from_row, from_col) = orientation.map(row, col, rows, columns);
+
+            for button in [Button::Up, Button::Down, Button::Left, Button::Right] {
+                let (dr, dc) = step(button);
+                let (to_row, to_col) = orientation.map(
+                    (row as isize + dr) as usize,
+                    (col as isize + dc) as usize,
+                    rows,
+                    columns,
+                );
+                let moved = (
+                    to_row as isize - from_row as isize,
+                    to_col as isize - from_col as isize,
+                );
+                assert_eq!(
+                    moved,
+                    step(orientation.map_button(button)),
+                    "{orientation:?} {button:?}"
+                );
+            }
+            assert_eq!(orientation.map_button(Button::Action), Button::Action);
+        }
+    }
+
+    #[test]
+    fn oriented_display_draws_within_the_physical_display() {
+        for orientation in all() {
+            let mut frame = Framebuffer::<3, 5>::new();
+            let mut display = OrientedDisplay::new(&mut frame, orientation);
+            let (rows, columns) = (display.rows(), display.columns());
+            assert_eq!((rows, columns), orientation.size(3, 5));
+
+            for row in 0..rows + 1 {
+                for col in 0..columns + 1 {
+                    display.set_pixel(row, col, Pixel::On);
+                }
+            }
+
+            let mut full = Framebuffer::<3, 5>::new();
+            full.fill(Pixel::On);
+            assert_eq!(frame, full, "{orientation:?}");
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Options the player can change on the settings screen of the [`GameMenu`](crate::menu::GameMenu),
+//! kept in a [`Storage`] so they survive a restart.
+
+use core::{ops::RangeInclusive, time::Duration};
+
+use crate::{
+    snapshot::{Reader, SnapshotError, Writer},
+    storage::{Storage, StorageError},
+};
+
+/// Namespace of the settings records in a [`Storage`], the difficulty of each game is stored under
+/// the name of the game
+pub const NAMESPACE: &str = "settings";
+
+/// Name of the record holding the [`Settings`]
+const SYSTEM_NAME: &str = "system";
+
+/// Version of the stored formats, increased whenever a format changes
+const FORMAT_VERSION: u8 = 1;
+
+/// The choices for [`Settings::attract_timeout`] with their labels
+pub const ATTRACT_TIMEOUTS: [(Option<Duration>, &str); 4] = [
+    (None, "OFF"),
+    (Some(Duration::from_secs(30)), "30S"),
+    (Some(Duration::from_secs(60)), "1M"),
+    (Some(Duration::from_secs(300)), "5M"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+        }
+    }
+
+    /// Scales the time between the steps of a game, a harder game steps more often. Rounds down to
+    /// whole milliseconds, the resolution of durations in a snapshot.
+    pub fn scale(&self, rate: Duration) -> Duration {
+        let rate = match self {
+            Difficulty::Easy => rate * 3 / 2,
+            Difficulty::Normal => rate,
+            Difficulty::Hard => rate * 2 / 3,
+        };
+        Duration::from_millis(rate.as_millis() as u64)
+    }
+
+    /// The times between steps `rate` scales to, from the hardest to the easiest difficulty
+    pub fn scaled_range(rate: Duration) -> RangeInclusive<Duration> {
+        Difficulty::Hard.scale(rate)..=Difficulty::Easy.scale(rate)
+    }
+
+    /// Reads the difficulty stored for the game `name`, if any
+    pub fn load<S: Storage + ?Sized>(
+        storage: &mut S,
+        name: &str,
+    ) -> Result<Option<Self>, StorageError> {
+        let mut buffer = [0; 2];
+        let Some(len) = storage.read(NAMESPACE, name, &mut buffer)? else {
+            return Ok(None);
+        };
+
+        let mut reader = Reader::new(&buffer[..len]);
+        if reader.u8()? != FORMAT_VERSION {
+            return Err(StorageError::Corrupt);
+        }
+        let difficulty = *Self::ALL
+            .get(reader.u8()? as usize)
+            .ok_or(StorageError::Corrupt)?;
+        Ok(Some(difficulty))
+    }
+
+    /// Stores the difficulty for the game `name`
+    pub fn save<S: Storage + ?Sized>(
+        &self,
+        storage: &mut S,
+        name: &str,
+    ) -> Result<(), StorageError> {
+        storage.write(NAMESPACE, name, &[FORMAT_VERSION, *self as u8])
+    }
+}
+
+/// How the display brings its dots up to date with each frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RefreshMode {
+    /// Only flip the dots that changed since the last frame
+    #[default]
+    Changes,
+    /// Drive every dot on every frame, slower but fixes dots that got stuck
+    Full,
+}
+
+impl RefreshMode {
+    pub const ALL: [RefreshMode; 2] = [RefreshMode::Changes, RefreshMode::Full];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RefreshMode::Changes => "CHANGES",
+            RefreshMode::Full => "FULL",
+        }
+    }
+}
+
+/// The options that apply to the whole system rather than a single game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub refresh: RefreshMode,
+    /// Time without input after which the selection screen starts cycling through the games,
+    /// `None` to never do so
+    pub attract_timeout: Option<Duration>,
+    pub sound: bool,
+}
+
+impl Settings {
+    pub const fn new() -> Self {
+        Self {
+            refresh: RefreshMode::Changes,
+            attract_timeout: Some(Duration::from_secs(60)),
+            sound: true,
+        }
+    }
+
+    /// The label of the current attract timeout, see [`ATTRACT_TIMEOUTS`]
+    pub fn attract_label(&self) -> &'static str {
+        ATTRACT_TIMEOUTS
+            .iter()
+            .find(|(timeout, _)| *timeout == self.attract_timeout)
+            .map_or("?", |(_, label)| label)
+    }
+
+    /// Replaces the settings with the stored ones, keeps them if none are stored
+    pub fn load<S: Storage + ?Sized>(&mut self, storage: &mut S) -> Result<(), StorageError> {
+        let mut buffer = [0; 8];
+        if let Some(len) = storage.read(NAMESPACE, SYSTEM_NAME, &mut buffer)? {
+            *self = Self::decode(&mut Reader::new(&buffer[..len]))?;
+        }
+        Ok(())
+    }
+
+    pub fn save<S: Storage + ?Sized>(&self, storage: &mut S) -> Result<(), StorageError> {
+        let mut buffer = [0; 8];
+        let mut writer = Writer::new(&mut buffer);
+        self.encode(&mut writer)?;
+        let len = writer.len();
+        storage.write(NAMESPACE, SYSTEM_NAME, &buffer[..len])
+    }
+
+    fn encode(&self, writer: &mut Writer) -> Result<(), SnapshotError> {
+        writer.u8(FORMAT_VERSION)?;
+        writer.u8(self.refresh as u8)?;
+        // whole seconds, zero when turned off
+        let timeout = self.attract_timeout.map_or(0, |timeout| timeout.as_secs());
+        writer.u16(u16::try_from(timeout).unwrap_or(u16::MAX))?;
+        writer.bool(self.sound)
+    }
+
+    fn decode(reader: &mut Reader) -> Result<Self, SnapshotError> {
+        if reader.u8()? != FORMAT_VERSION {
+            return Err(SnapshotError::Invalid);
+        }
+        let refresh = *RefreshMode::ALL
+            .get(reader.u8()? as usize)
+            .ok_or(SnapshotError::Invalid)?;
+        let timeout = reader.u16()?;
+        let sound = reader.bool()?;
+
+        Ok(Self {
+            refresh,
+            attract_timeout: (timeout > 0).then(|| Duration::from_secs(timeout as u64)),
+            sound,
+        })
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,449 @@
+use core::time::Duration;
+
+use crate::{
+    Game, GameState, Icon, RandomNumberSource,
+    display::{Pixel, PixelDisplay},
+    events::{Cue, GameEvent, GameEventBuffer},
+    font::{Font, LargeDigits, Monospace, Proportional, Tiny},
+    highscore::{HighScore, HighScores, INITIALS},
+    input::Input,
+    layout::{self, Align, Rect},
+    marquee::Marquee,
+    number::Number,
+    settings::Difficulty,
+    snapshot::{self, Reader, Snapshot, SnapshotError, Writer},
+};
+
+/// Time the title and game over screens ignore the action button, so a button mashed during play
+/// does not immediately start or restart the game
+const START_DELAY: Duration = Duration::from_millis(1000);
+
+/// Time each number of the countdown is shown
+const COUNTDOWN_STEP: Duration = Duration::from_millis(500);
+
+/// The number the countdown starts from
+const COUNTDOWN_FROM: u32 = 3;
+
+/// Time the score takes to count up on the game over screen
+const COUNT_UP_TIME: Duration = Duration::from_millis(1000);
+
+/// Whether a round of a [`PlayLoop`] continues after an update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Continue,
+    GameOver,
+}
+
+/// The core of a game, run by a [`GameShell`] which takes care of the title, countdown and game
+/// over screens around it
+pub trait PlayLoop<I: Input, R: RandomNumberSource> {
+    /// Adapts the game to the size of the display, called before every update. A game starts a new
+    /// round when the size changes. Boards are [`Grid`](crate::grid::Grid)s, which hold at most
+    /// [`MAX_CELLS`](crate::grid::MAX_CELLS) cells: on a larger display the rows beyond that are not
+    /// played on.
+    fn fit(&mut self, rows: usize, columns: usize);
+
+    /// Runs the logic of the game, like [`Game::update`], pushing what happened into `events`. The
+    /// shell emits the events for the start and end of a round itself.
+    fn play(
+        &mut self,
+        elapsed: Duration,
+        input: &I,
+        random: &mut R,
+        events: &mut GameEventBuffer,
+    ) -> Outcome;
+
+    /// Draws the game to the display, like [`Game::render`]
+    fn render(&mut self, display: &mut dyn PixelDisplay);
+
+    /// True if the game changed since it was last rendered, see [`Game::is_dirty`]
+    fn is_dirty(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str;
+
+    fn icon(&self) -> Icon;
+
+    /// The score of the current (or last) round
+    fn score(&self) -> usize;
+
+    /// Prepares a new round
+    fn reset(&mut self);
+
+    /// Called when the game is paused, see [`Game::on_pause`]
+    fn on_pause(&mut self) {}
+
+    /// Called when the game continues after being paused
+    fn on_resume(&mut self) {}
+
+    /// See [`Game::difficulty`]
+    fn difficulty(&self) -> Option<Difficulty> {
+        None
+    }
+
+    fn set_difficulty(&mut self, _difficulty: Difficulty) {}
+
+    /// The state of the round, for games that can be saved and continued later
+    fn snapshot(&self) -> Option<&dyn Snapshot> {
+        None
+    }
+
+    fn snapshot_mut(&mut self) -> Option<&mut dyn Snapshot> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Title,
+    Countdown,
+    Playing,
+    GameOver,
+    /// The player picks the letters to put next to a new high score
+    EnterInitials,
+}
+
+/// Runs a [`PlayLoop`] as a [`Game`]: shows a title screen, counts down before each round, shows
+/// the score (and whether it is a new best) when the round is over and lets the player enter their
+/// initials for a high score
+pub struct GameShell<G> {
+    game: G,
+    phase: Phase,
+    /// Time spent in the current phase
+    phase_time: Duration,
+    high_scores: HighScores,
+    new_best: bool,
+    badge: Option<Marquee<'static>>,
+    /// The initials being entered, starting with the ones entered last time
+    initials: [u8; INITIALS],
+    /// Index of the letter of the initials being picked
+    cursor: usize,
+    /// True when the screen around the game changed since it was last rendered
+    dirty: bool,
+    events: GameEventBuffer,
+}
+
+impl<G> GameShell<G> {
+    pub fn new(game: G) -> Self {
+        Self {
+            game,
+            phase: Phase::Title,
+            phase_time: Duration::ZERO,
+            high_scores: HighScores::new(),
+            new_best: false,
+            badge: None,
+            initials: *b"AAA",
+            cursor: 0,
+            dirty: true,
+            events: GameEventBuffer::new(),
+        }
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn best(&self) -> usize {
+        self.high_scores.best().unwrap_or(0)
+    }
+
+    fn enter(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.phase_time = Duration::ZERO;
+        self.dirty = true;
+    }
+
+    fn draw_title(&self, display: &mut dyn PixelDisplay, icon: Icon) {
+        let (title, rest) = Rect::of(display).split_top(layout::line_height(&Monospace));
+        layout::draw_aligned(display, &Monospace, title, Align::Center, "RDY");
+
+        let row = rest.row + (rest.height.saturating_sub(icon.height()) / 2) as isize;
+        let col = (rest.width as isize - icon.width as isize) / 2;
+        display.draw_glyph(row, col, &icon.glyph());
+    }
+
+    /// Index of the number currently shown by the countdown
+    fn countdown_step(&self) -> u32 {
+        (self.phase_time.as_millis() / COUNTDOWN_STEP.as_millis()) as u32
+    }
+
+    fn draw_countdown(&self, display: &mut dyn PixelDisplay) {
+        let number =
+            Number::unsigned(COUNTDOWN_FROM.saturating_sub(self.countdown_step()) as usize);
+
+        let font: &dyn Font = if display.rows() >= LargeDigits.height() {
+            &LargeDigits
+        } else {
+            &Monospace
+        };
+        let row = (display.rows().saturating_sub(font.height()) / 2) as isize;
+        let area = Rect::new(row, 0, font.height(), display.columns());
+        layout::draw_aligned(display, font, area, Align::Center, number.as_str());
+    }
+
+    /// The score shown while counting up from zero to the final score
+    fn counted_score(&self, score: usize) -> usize {
+        if self.phase_time >= COUNT_UP_TIME {
+            return score;
+        }
+        (score as u128 * self.phase_time.as_micros() / COUNT_UP_TIME.as_micros()) as usize
+    }
+
+    fn draw_game_over(&self, display: &mut dyn PixelDisplay, score: usize) {
+        let fonts: [&dyn Font; 3] = [&Monospace, &Proportional, &Tiny];
+        let (title, rest) = Rect::of(display).split_top(layout::line_height(&Monospace));
+
+        match &self.badge {
+            Some(badge) => badge.draw(display),
+            None => {
+                let font = layout::fitting_font(&fonts, "DEAD", title.width);
+                layout::draw_aligned(display, font, title, Align::Center, "DEAD");
+            }
+        }
+
+        // show the full score if it fits in any font, otherwise fall back to the compact notation
+        let shown = self.counted_score(score);
+        let mut score = Number::unsigned(shown);
+        if layout::text_width(&Tiny, score.as_str()) > rest.width {
+            score = Number::compact(shown);
+        }
+        let font = layout::fitting_font(&fonts, score.as_str(), rest.width);
+        layout::draw_aligned(display, font, rest, Align::Center, score.as_str());
+    }
+
+    /// Lets the player pick the initials, returns true once the score was added to the high scores
+    fn update_initials<I: Input>(&mut self, input: &I, score: usize) -> bool {
+        let letter = &mut self.initials[self.cursor];
+        if input.up() {
+            *letter = if *letter >= b'Z' { b'A' } else { *letter + 1 };
+        }
+        if input.down() {
+            *letter = if *letter <= b'A' { b'Z' } else { *letter - 1 };
+        }
+        if input.left() {
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+        if input.right() {
+            self.cursor = (self.cursor + 1).min(INITIALS - 1);
+        }
+
+        if input.action() {
+            self.cursor += 1;
+            if self.cursor == INITIALS {
+                self.high_scores.insert(HighScore {
+                    initials: self.initials,
+                    score,
+                });
+                self.cursor = 0;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn draw_initials(&self, display: &mut dyn PixelDisplay, score: usize) {
+        let (title, rest) = Rect::of(display).split_top(layout::line_height(&Monospace));
+        let initials = core::str::from_utf8(&self.initials).unwrap_or_default();
+        layout::draw_aligned(display, &Monospace, title, Align::Center, initials);
+
+        // underline the letter being picked, on the last row of the line
+        let letter_width = Monospace.glyph(' ').advance as isize;
+        let start = (title.width as isize - layout::text_width(&Monospace, initials) as isize) / 2;
+        let row = title.row + Monospace.height() as isize - 1;
+        for i in 0..letter_width - 1 {
+            let col = start + self.cursor as isize * letter_width + i;
+            if row >= 0 && col >= 0 {
+                display.set_pixel(row as usize, col as usize, Pixel::On);
+            }
+        }
+
+        let fonts: [&dyn Font; 3] = [&Monospace, &Proportional, &Tiny];
+        let mut score_text = Number::unsigned(score);
+        if layout::text_width(&Tiny, score_text.as_str()) > rest.width {
+            score_text = Number::compact(score);
+        }
+        let font = layout::fitting_font(&fonts, score_text.as_str(), rest.width);
+        layout::draw_aligned(display, font, rest, Align::Center, score_text.as_str());
+    }
+}
+
+impl<G: Default> Default for GameShell<G> {
+    fn default() -> Self {
+        Self::new(G::default())
+    }
+}
+
+impl<G: PlayLoop<I, R>, I: Input, R: RandomNumberSource> Game<I, R> for GameShell<G> {
+    fn update(&mut self, elapsed: Duration, input: &I, display: &dyn PixelDisplay, random: &mut R) {
+        self.game.fit(display.rows(), display.columns());
+
+        let step = self.countdown_step();
+        let counted = self.counted_score(self.game.score());
+        self.phase_time += elapsed;
+        self.events.clear();
+
+        match self.phase {
+            Phase::Title => {
+                if input.action() && self.phase_time > START_DELAY {
+                    self.enter(Phase::Countdown);
+                }
+            }
+            Phase::Countdown => {
+                self.dirty |= self.countdown_step() != step;
+
+                if self.phase_time >= COUNTDOWN_STEP * COUNTDOWN_FROM {
+                    self.events.push(GameEvent::SoundCue(Cue::Start));
+                    self.enter(Phase::Playing);
+                }
+            }
+            Phase::Playing => {
+                if self.game.play(elapsed, input, random, &mut self.events) == Outcome::GameOver {
+                    self.events.push(GameEvent::Died);
+                    self.events.push(GameEvent::SoundCue(Cue::Die));
+                    self.new_best = self.game.score() > self.best();
+                    self.badge = None;
+                    self.enter(Phase::GameOver);
+                }
+            }
+            Phase::GameOver => {
+                self.dirty |= self.counted_score(self.game.score()) != counted;
+
+                // the badge replaces the title once the score has been counted up
+                if self.new_best && self.badge.is_none() && self.phase_time >= COUNT_UP_TIME {
+                    let fonts: [&'static dyn Font; 3] = [&Monospace, &Proportional, &Tiny];
+                    let (title, _) = Rect::of(display).split_top(layout::line_height(&Monospace));
+                    let font = layout::fitting_font(&fonts, "NEW BEST", title.width);
+                    self.badge = Some(Marquee::new("NEW BEST", title).font(font).speed(20));
+                    self.dirty = true;
+                }
+                if let Some(badge) = self.badge.as_mut() {
+                    self.dirty |= badge.update(elapsed);
+                }
+
+                if input.action() && self.phase_time > START_DELAY {
+                    if self.high_scores.qualifies(self.game.score()) {
+                        self.cursor = 0;
+                        self.enter(Phase::EnterInitials);
+                    } else {
+                        Game::<I, R>::reset(self);
+                    }
+                }
+            }
+            Phase::EnterInitials => {
+                self.dirty |= input.any();
+
+                let score = self.game.score();
+                if self.update_initials(input, score) {
+                    Game::<I, R>::reset(self);
+                }
+            }
+        }
+    }
+
+    fn render(&mut self, display: &mut dyn PixelDisplay) {
+        match self.phase {
+            Phase::Title => {
+                display.clear();
+                self.draw_title(display, self.game.icon());
+            }
+            Phase::Countdown => {
+                display.clear();
+                self.draw_countdown(display);
+            }
+            Phase::Playing => self.game.render(display),
+            Phase::GameOver => {
+                display.clear();
+                self.draw_game_over(display, self.game.score());
+            }
+            Phase::EnterInitials => {
+                display.clear();
+                self.draw_initials(display, self.game.score());
+            }
+        }
+        self.dirty = false;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty || (self.phase == Phase::Playing && self.game.is_dirty())
+    }
+
+    fn events(&self) -> &[GameEvent] {
+        self.events.as_slice()
+    }
+
+    fn state(&self) -> GameState {
+        match self.phase {
+            Phase::Title => GameState::Start,
+            Phase::Countdown | Phase::Playing => GameState::Playing,
+            Phase::GameOver | Phase::EnterInitials => GameState::GameOver,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        self.game.name()
+    }
+
+    fn icon(&self) -> Icon {
+        self.game.icon()
+    }
+
+    fn score(&self) -> Option<usize> {
+        Some(self.game.score())
+    }
+
+    fn high_scores(&self) -> Option<&HighScores> {
+        Some(&self.high_scores)
+    }
+
+    fn high_scores_mut(&mut self) -> Option<&mut HighScores> {
+        Some(&mut self.high_scores)
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        self.game.difficulty()
+    }
+
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.game.set_difficulty(difficulty);
+    }
+
+    fn save(&self, writer: &mut Writer) -> Result<bool, SnapshotError> {
+        if !matches!(self.phase, Phase::Countdown | Phase::Playing) {
+            return Ok(false);
+        }
+        let Some(state) = self.game.snapshot() else {
+            return Ok(false);
+        };
+
+        snapshot::save(state, writer)?;
+        Ok(true)
+    }
+
+    /// Restores the round and counts down before it continues
+    fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+        let state = self.game.snapshot_mut().ok_or(SnapshotError::Unsupported)?;
+        snapshot::restore(state, reader)?;
+
+        self.new_best = false;
+        self.badge = None;
+        self.enter(Phase::Countdown);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.game.reset();
+        self.new_best = false;
+        self.badge = None;
+        self.enter(Phase::Title);
+    }
+
+    fn on_pause(&mut self) {
+        self.game.on_pause();
+    }
+
+    fn on_resume(&mut self) {
+        self.game.on_resume();
+    }
+}
@@ -1,30 +1,42 @@
 use core::time::Duration;
 
 use crate::{
-    Game, RandomNumberSource,
+    Icon, RandomNumberSource,
     display::{Pixel, PixelDisplay},
+    events::{Cue, GameEvent, GameEventBuffer},
+    grid::{Grid, MAX_CELLS},
     input::Input,
+    settings::Difficulty,
+    shell::{Outcome, PlayLoop},
+    snapshot::{Reader, Snapshot, SnapshotError, Writer},
 };
 
-pub struct SnakeGame<const ROWS: usize, const COLS: usize> {
-    state: State,
+/// Version of the snapshot format, see [`Snapshot`]
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// Time between the steps of a new round at the normal difficulty
+const START_RATE: Duration = Duration::from_millis(400);
+
+/// The shortest time between steps, no matter how much the snake ate
+const MIN_RATE: Duration = Duration::from_millis(50);
+
+/// How much shorter the time between steps gets with every apple
+const RATE_STEP: Duration = Duration::from_millis(10);
+
+pub struct SnakeGame {
     update_timer: Duration,
     update_rate: Duration,
+    difficulty: Difficulty,
     position_x: isize,
     position_y: isize,
     apple_position_x: isize,
     apple_position_y: isize,
-    board: [[isize; COLS]; ROWS],
+    /// The steps left until each part of the body moves on, zero for free cells
+    board: Grid<u16>,
     direction: Direction,
     length: usize,
-    state_wait_timer: Duration,
-}
-
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum State {
-    PreStart,
-    Running,
-    GameOver,
+    /// True when the board changed since it was last rendered
+    dirty: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -36,6 +48,13 @@ enum Direction {
 }
 
 impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
     fn dx(&self) -> isize {
         match *self {
             Direction::Up => 0,
@@ -64,65 +83,127 @@ impl Direction {
     }
 }
 
-impl<const ROWS: usize, const COLS: usize> SnakeGame<ROWS, COLS> {
+impl SnakeGame {
+    /// A game without a board, it gets sized by [`PlayLoop::fit`]
     pub fn new() -> Self {
         Self {
-            state: State::PreStart,
             update_timer: Duration::ZERO,
-            update_rate: Duration::from_millis(400),
-            position_x: COLS as isize / 2,
-            position_y: ROWS as isize / 2,
+            update_rate: START_RATE,
+            difficulty: Difficulty::Normal,
+            position_x: 0,
+            position_y: 0,
             apple_position_x: 5,
             apple_position_y: 5,
-            board: [[0; COLS]; ROWS],
+            board: Grid::new(0),
             direction: Direction::Up,
             length: 0,
-            state_wait_timer: Duration::ZERO,
+            dirty: true,
         }
     }
+
+    pub fn with_difficulty(difficulty: Difficulty) -> Self {
+        Self {
+            update_rate: difficulty.scale(START_RATE),
+            difficulty,
+            ..Self::new()
+        }
+    }
+
+    /// Starts a new round on an empty board of the given size
+    fn start(&mut self, rows: usize, columns: usize) {
+        *self = Self::with_difficulty(self.difficulty);
+        self.board.resize(rows, columns, 0);
+        self.position_x = self.board.columns() as isize / 2;
+        self.position_y = self.board.rows() as isize / 2;
+    }
 }
 
-impl<const ROWS: usize, const COLS: usize> Default for SnakeGame<ROWS, COLS> {
+impl Default for SnakeGame {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<const ROWS: usize, const COLS: usize, I: Input, D: PixelDisplay, R: RandomNumberSource>
-    Game<I, D, R> for SnakeGame<ROWS, COLS>
-{
-    fn update(&mut self, elapsed: Duration, input: &I, display: &mut D, random: &mut R) {
-        if self.state == State::PreStart {
-            display.clear();
-            display.draw_text(0, 0, "RDY");
-            display.draw_text(8, COLS as isize / 2 - 3, "S");
+/// Stores the board size, timers, positions, direction and length followed by the board, one u16
+/// per cell
+impl Snapshot for SnakeGame {
+    fn version(&self) -> u8 {
+        SNAPSHOT_VERSION
+    }
+
+    fn save(&self, writer: &mut Writer) -> Result<(), SnapshotError> {
+        writer.u16(self.board.rows() as u16)?;
+        writer.u16(self.board.columns() as u16)?;
+        writer.duration(self.update_timer)?;
+        writer.duration(self.update_rate)?;
+        writer.i16(self.position_x as i16)?;
+        writer.i16(self.position_y as i16)?;
+        writer.i16(self.apple_position_x as i16)?;
+        writer.i16(self.apple_position_y as i16)?;
+        writer.u8(self.direction as u8)?;
+        writer.u16(self.length as u16)?;
+
+        for cell in self.board.cells() {
+            writer.u16(*cell)?;
+        }
+        Ok(())
+    }
+
+    fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+        let rows = reader.u16()? as usize;
+        let columns = reader.u16()? as usize;
+        if rows * columns > MAX_CELLS {
+            return Err(SnapshotError::Invalid);
+        }
 
-            // delay for starting the game
-            self.state_wait_timer += elapsed;
+        let mut game = Self::new();
+        game.difficulty = self.difficulty;
+        game.board.resize(rows, columns, 0);
+        game.update_timer = reader.duration()?;
+        game.update_rate = reader.duration()?;
+        game.position_x = reader.i16()? as isize;
+        game.position_y = reader.i16()? as isize;
+        game.apple_position_x = reader.i16()? as isize;
+        game.apple_position_y = reader.i16()? as isize;
+        game.direction = *Direction::ALL
+            .get(reader.u8()? as usize)
+            .ok_or(SnapshotError::Invalid)?;
+        game.length = reader.u16()? as usize;
+
+        for cell in game.board.cells_mut() {
+            *cell = reader.u16()?;
+        }
 
-            if input.action() && self.state_wait_timer > Duration::from_millis(1000) {
-                // moving on, reset the timer (for use by the game over state)
-                self.state_wait_timer = Duration::ZERO;
+        // the head has to be on the board, the apple either on the board or not spawned yet (-1)
+        let on_board = |x: isize, y: isize| game.board.contains(y, x);
+        if !on_board(game.position_x, game.position_y)
+            || !(on_board(game.apple_position_x, game.apple_position_y)
+                || game.apple_position_x < 0)
+            || !(MIN_RATE..=*Difficulty::scaled_range(START_RATE).end()).contains(&game.update_rate)
+            || game.update_timer >= game.update_rate
+        {
+            return Err(SnapshotError::Invalid);
+        }
 
-                self.state = State::Running;
-            }
-            return;
-        } else if self.state == State::GameOver {
-            display.clear();
-            display.draw_text(0, 0, "DEAD");
-            display.draw_text(8, 0, "=");
-            display.draw_number(8, 10, self.length);
-
-            // delay for leaving the game over state
-            self.state_wait_timer += elapsed;
-
-            if input.action() && self.state_wait_timer > Duration::from_millis(1000) {
-                *self = SnakeGame::new(); // restart by reinstantiating self ;)
-            }
+        *self = game;
+        Ok(())
+    }
+}
 
-            return;
-        } // else continue with the game logic
+impl<I: Input, R: RandomNumberSource> PlayLoop<I, R> for SnakeGame {
+    fn fit(&mut self, rows: usize, columns: usize) {
+        if !self.board.fits(rows, columns) {
+            self.start(rows, columns);
+        }
+    }
 
+    fn play(
+        &mut self,
+        elapsed: Duration,
+        input: &I,
+        random: &mut R,
+        events: &mut GameEventBuffer,
+    ) -> Outcome {
         self.update_timer += elapsed;
 
         let new_direction = if input.left() {
@@ -144,62 +225,70 @@ impl<const ROWS: usize, const COLS: usize, I: Input, D: PixelDisplay, R: RandomN
         // spawn apple in random position (not on snake itself) if unspecified
         if self.apple_position_x < 0 {
             loop {
-                self.apple_position_x = (random.next_u32() as usize % COLS) as isize;
-                self.apple_position_y = (random.next_u32() as usize % ROWS) as isize;
-
-                if self.board[self.apple_position_y as usize][self.apple_position_x as usize] == 0 {
+                self.apple_position_x = random.next_index(self.board.columns()) as isize;
+                self.apple_position_y = random.next_index(self.board.rows()) as isize;
+
+                if self.board.get(
+                    self.apple_position_y as usize,
+                    self.apple_position_x as usize,
+                ) == 0
+                {
                     break;
                 }
             }
+            self.dirty = true;
         }
 
-        if self.update_timer > self.update_rate {
+        // take as many steps as are due, so the speed does not depend on how often this is called
+        while self.update_timer >= self.update_rate {
             self.update_timer -= self.update_rate;
+            self.dirty = true;
 
             // update board
 
-            for r in 0..ROWS {
-                for c in 0..COLS {
-                    self.board[r][c] -= 1;
-
-                    if self.board[r][c] < 0 {
-                        self.board[r][c] = 0;
-                    }
-                }
+            for cell in self.board.cells_mut() {
+                *cell = cell.saturating_sub(1);
             }
-            self.board[self.position_y as usize][self.position_x as usize] = self.length as isize;
+            self.board.set(
+                self.position_y as usize,
+                self.position_x as usize,
+                self.length as u16,
+            );
 
             // move head in current direction
             self.position_x += self.direction.dx();
             self.position_y += self.direction.dy();
 
             // check for collision with the walls
-            if self.position_x < 0
-                || self.position_y < 0
-                || self.position_x >= COLS as isize
-                || self.position_y >= ROWS as isize
-            {
-                self.state = State::GameOver;
-                return;
+            if !self.board.contains(self.position_y, self.position_x) {
+                return Outcome::GameOver;
             }
 
             // check for collision with the apple
             if self.position_x == self.apple_position_x && self.position_y == self.apple_position_y
             {
-                self.update_rate =
-                    Duration::from_millis(50).max(self.update_rate - Duration::from_millis(10));
+                self.update_rate = MIN_RATE.max(self.update_rate.saturating_sub(RATE_STEP));
                 self.length += 1;
+                events.push(GameEvent::ScoreChanged(self.length));
+                events.push(GameEvent::SoundCue(Cue::Eat));
                 self.apple_position_x = -1;
                 self.apple_position_y = -1;
             }
             // check for collision with self
-            if self.board[self.position_y as usize][self.position_x as usize] > 0 {
+            if self
+                .board
+                .get(self.position_y as usize, self.position_x as usize)
+                > 0
+            {
                 // TODO: CRASH!!!
-                self.state = State::GameOver;
+                return Outcome::GameOver;
             }
         }
 
-        // redraw
+        Outcome::Continue
+    }
+
+    fn render(&mut self, display: &mut dyn PixelDisplay) {
         display.clear();
 
         display.set_pixel(
@@ -214,20 +303,136 @@ impl<const ROWS: usize, const COLS: usize, I: Input, D: PixelDisplay, R: RandomN
                 Pixel::On,
             );
         }
-        for r in 0..ROWS {
-            for c in 0..COLS {
-                if self.board[r][c] > 0 {
-                    display.set_pixel(r, c, Pixel::On);
-                }
+        for (r, c, cell) in self.board.iter() {
+            if cell > 0 {
+                display.set_pixel(r, c, Pixel::On);
             }
         }
+        self.dirty = false;
     }
 
-    fn state(&self) -> crate::GameState {
-        match self.state {
-            State::PreStart => crate::GameState::Start,
-            State::Running => crate::GameState::Playing,
-            State::GameOver => crate::GameState::GameOver,
-        }
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn name(&self) -> &'static str {
+        "SNAKE"
+    }
+
+    fn icon(&self) -> Icon {
+        Icon::new(&[0x00, 0x3E, 0x02, 0x5E, 0x10, 0x1E, 0x02, 0x00], 8)
+    }
+
+    fn score(&self) -> usize {
+        self.length
+    }
+
+    fn reset(&mut self) {
+        self.start(self.board.rows(), self.board.columns());
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        Some(self.difficulty)
+    }
+
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.update_rate = difficulty.scale(START_RATE);
+    }
+
+    fn snapshot(&self) -> Option<&dyn Snapshot> {
+        Some(self)
+    }
+
+    fn snapshot_mut(&mut self) -> Option<&mut dyn Snapshot> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input::BasicInput,
+        random::Pcg32,
+        snapshot::tests::{check_snapshot, corrupted, restored},
+    };
+
+    /// A game on an 8x10 board after a few steps to the right
+    fn played() -> SnakeGame {
+        let mut game = SnakeGame::new();
+        let mut random = Pcg32::new(7);
+        PlayLoop::<BasicInput, Pcg32>::fit(&mut game, 8, 10);
+        let input = BasicInput {
+            right: true,
+            ..Default::default()
+        };
+        let outcome = game.play(
+            Duration::from_millis(1000),
+            &input,
+            &mut random,
+            &mut GameEventBuffer::new(),
+        );
+        assert!(matches!(outcome, Outcome::Continue));
+        game
+    }
+
+    /// Offsets into a snapshot, after the version byte
+    const TIMER: usize = 5;
+    const RATE: usize = 9;
+    const HEAD_X: usize = 13;
+    const DIRECTION: usize = 21;
+
+    #[test]
+    fn snapshot_round_trips() {
+        let game = played();
+        let data = check_snapshot(played);
+        assert_eq!(data.len(), 24 + 2 * 8 * 10);
+
+        let restored = restored(SnakeGame::new(), &data).unwrap();
+        assert_eq!(restored.board.rows(), 8);
+        assert_eq!(restored.position_x, game.position_x);
+        assert_eq!(restored.update_timer, game.update_timer);
+    }
+
+    #[test]
+    fn corrupt_snapshots_are_rejected() {
+        let data = check_snapshot(played);
+        let corrupt = |patches: &[(usize, &[u8])]| corrupted(played(), &data, patches);
+        let millis = |ms: u32| ms.to_le_bytes();
+
+        // board larger than a grid holds
+        assert_eq!(corrupt(&[(1, &[0, 1, 0, 1])]), Some(SnapshotError::Invalid));
+        // head outside of the board
+        assert_eq!(
+            corrupt(&[(HEAD_X, &10i16.to_le_bytes())]),
+            Some(SnapshotError::Invalid)
+        );
+        assert_eq!(
+            corrupt(&[(HEAD_X, &(-1i16).to_le_bytes())]),
+            Some(SnapshotError::Invalid)
+        );
+        // no such direction
+        assert_eq!(corrupt(&[(DIRECTION, &[4])]), Some(SnapshotError::Invalid));
+        // step rates no difficulty reaches, including zero which would never finish a step
+        assert_eq!(corrupt(&[(RATE, &millis(0))]), Some(SnapshotError::Invalid));
+        assert_eq!(
+            corrupt(&[(RATE, &millis(49))]),
+            Some(SnapshotError::Invalid)
+        );
+        let slowest = Difficulty::scaled_range(START_RATE).end().as_millis() as u32;
+        assert_eq!(
+            corrupt(&[(RATE, &millis(slowest + 1))]),
+            Some(SnapshotError::Invalid)
+        );
+        // a step that is overdue
+        assert_eq!(
+            corrupt(&[(TIMER, &millis(400))]),
+            Some(SnapshotError::Invalid)
+        );
+
+        let no_time = (TIMER, &millis(0)[..]);
+        assert_eq!(corrupt(&[no_time, (RATE, &millis(50))]), None);
+        assert_eq!(corrupt(&[no_time, (RATE, &millis(slowest))]), None);
     }
 }
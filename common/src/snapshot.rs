@@ -0,0 +1,395 @@
+//! A compact binary format for saving the state of a game in progress and continuing it later.
+//!
+//! Each snapshot starts with the version of the game's format, followed by whatever the game writes.
+//! All numbers are little-endian.
+
+use core::time::Duration;
+
+use crate::storage::StorageError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The buffer is too small for the snapshot
+    BufferFull,
+    /// The snapshot ended before all of the state was read
+    UnexpectedEnd,
+    /// The snapshot was written by a different version of the game
+    UnsupportedVersion(u8),
+    /// The snapshot contains values that are not valid for the game, e.g. a different board size
+    Invalid,
+    /// The game does not support saving and restoring
+    Unsupported,
+}
+
+impl From<SnapshotError> for StorageError {
+    fn from(error: SnapshotError) -> Self {
+        match error {
+            SnapshotError::BufferFull => StorageError::TooLarge,
+            _ => StorageError::Corrupt,
+        }
+    }
+}
+
+/// State that can be saved into and restored from a snapshot
+pub trait Snapshot {
+    /// Version of the format written by [`Snapshot::save`], increase it whenever the format changes
+    fn version(&self) -> u8;
+
+    fn save(&self, writer: &mut Writer) -> Result<(), SnapshotError>;
+
+    /// Replaces the state with the one read from a snapshot of the current version. Leaves the state
+    /// unchanged if the snapshot is not valid.
+    fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError>;
+}
+
+/// Writes the version of `state` followed by its snapshot
+pub fn save(state: &dyn Snapshot, writer: &mut Writer) -> Result<(), SnapshotError> {
+    writer.u8(state.version())?;
+    state.save(writer)
+}
+
+/// Restores `state` from a snapshot written by [`save`]
+pub fn restore(state: &mut dyn Snapshot, reader: &mut Reader) -> Result<(), SnapshotError> {
+    let version = reader.u8()?;
+    if version != state.version() {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    state.restore(reader)
+}
+
+pub struct Writer<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position == 0
+    }
+
+    /// Drops everything written after the first `len` bytes
+    pub fn truncate(&mut self, len: usize) {
+        self.position = self.position.min(len);
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+        let end = self.position + bytes.len();
+        self.buffer
+            .get_mut(self.position..end)
+            .ok_or(SnapshotError::BufferFull)?
+            .copy_from_slice(bytes);
+        self.position = end;
+        Ok(())
+    }
+
+    pub fn u8(&mut self, value: u8) -> Result<(), SnapshotError> {
+        self.bytes(&[value])
+    }
+
+    pub fn bool(&mut self, value: bool) -> Result<(), SnapshotError> {
+        self.u8(value as u8)
+    }
+
+    pub fn u16(&mut self, value: u16) -> Result<(), SnapshotError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn i16(&mut self, value: i16) -> Result<(), SnapshotError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u32(&mut self, value: u32) -> Result<(), SnapshotError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    /// Writes a duration in whole milliseconds, saturating at about 49 days
+    pub fn duration(&mut self, value: Duration) -> Result<(), SnapshotError> {
+        self.u32(u32::try_from(value.as_millis()).unwrap_or(u32::MAX))
+    }
+}
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .ok_or(SnapshotError::UnexpectedEnd)?;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or(SnapshotError::UnexpectedEnd)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, SnapshotError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Invalid),
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16, SnapshotError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn i16(&mut self) -> Result<i16, SnapshotError> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn duration(&mut self) -> Result<Duration, SnapshotError> {
+        Ok(Duration::from_millis(self.u32()? as u64))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// The snapshot of `state`, including its version
+    pub(crate) fn saved(state: &dyn Snapshot) -> Vec<u8> {
+        let mut buffer = [0; 4096];
+        let mut writer = Writer::new(&mut buffer);
+        save(state, &mut writer).unwrap();
+        let len = writer.len();
+        buffer[..len].into()
+    }
+
+    /// Restores `state` from `data`, checking that it is left unchanged if that fails
+    pub(crate) fn restored<S: Snapshot>(mut state: S, data: &[u8]) -> Result<S, SnapshotError> {
+        let before = saved(&state);
+        let result = restore(&mut state, &mut Reader::new(data));
+        if result.is_err() {
+            assert_eq!(saved(&state), before, "failed restore changed the state");
+        }
+        result.map(|_| state)
+    }
+
+    /// Checks that the snapshot of the state returned by `played` restores into another such state,
+    /// and that truncated snapshots and other versions are rejected. Returns the snapshot.
+    pub(crate) fn check_snapshot<S: Snapshot>(played: impl Fn() -> S) -> Vec<u8> {
+        let data = saved(&played());
+        let restored_state = restored(played(), &data).unwrap();
+        assert_eq!(saved(&restored_state), data);
+
+        for len in 0..data.len() {
+            assert_eq!(
+                restored(played(), &data[..len]).err(),
+                Some(SnapshotError::UnexpectedEnd),
+                "{len} bytes"
+            );
+        }
+
+        let mut other = data.clone();
+        other[0] = other[0].wrapping_add(1);
+        assert_eq!(
+            restored(played(), &other).err(),
+            Some(SnapshotError::UnsupportedVersion(other[0]))
+        );
+
+        data
+    }
+
+    /// Restores `state` from `data` with the given bytes replaced at each offset, returns the error
+    pub(crate) fn corrupted<S: Snapshot>(
+        state: S,
+        data: &[u8],
+        patches: &[(usize, &[u8])],
+    ) -> Option<SnapshotError> {
+        let mut data = Vec::from(data);
+        for (offset, bytes) in patches {
+            data[*offset..offset + bytes.len()].copy_from_slice(bytes);
+        }
+        restored(state, &data).err()
+    }
+
+    /// A single counter, version 3
+    #[derive(Debug, PartialEq)]
+    struct Counter(u16);
+
+    impl Snapshot for Counter {
+        fn version(&self) -> u8 {
+            3
+        }
+
+        fn save(&self, writer: &mut Writer) -> Result<(), SnapshotError> {
+            writer.u16(self.0)
+        }
+
+        fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+            self.0 = reader.u16()?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn values_read_back_as_written() {
+        let mut buffer = [0; 32];
+        let mut writer = Writer::new(&mut buffer);
+        writer.u8(0xAB).unwrap();
+        writer.bool(true).unwrap();
+        writer.u16(0xBEEF).unwrap();
+        writer.i16(-1234).unwrap();
+        writer.u32(0xDEAD_BEEF).unwrap();
+        writer.duration(Duration::from_micros(1_500_900)).unwrap();
+        writer.bytes(b"xyz").unwrap();
+        let len = writer.len();
+        assert_eq!(len, 1 + 1 + 2 + 2 + 4 + 4 + 3);
+
+        let mut reader = Reader::new(&buffer[..len]);
+        assert_eq!(reader.u8(), Ok(0xAB));
+        assert_eq!(reader.bool(), Ok(true));
+        assert_eq!(reader.u16(), Ok(0xBEEF));
+        assert_eq!(reader.i16(), Ok(-1234));
+        assert_eq!(reader.u32(), Ok(0xDEAD_BEEF));
+        // durations are stored in whole milliseconds
+        assert_eq!(reader.duration(), Ok(Duration::from_millis(1500)));
+        assert_eq!(reader.bytes(3), Ok(&b"xyz"[..]));
+        assert_eq!(reader.u8(), Err(SnapshotError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn numbers_are_little_endian() {
+        let mut buffer = [0; 4];
+        Writer::new(&mut buffer).u32(0x0403_0201).unwrap();
+        assert_eq!(buffer, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn long_durations_saturate() {
+        let mut buffer = [0; 4];
+        Writer::new(&mut buffer)
+            .duration(Duration::from_secs(u64::MAX))
+            .unwrap();
+        assert_eq!(
+            Reader::new(&buffer).duration(),
+            Ok(Duration::from_millis(u32::MAX as u64))
+        );
+    }
+
+    #[test]
+    fn writing_past_the_end_fails() {
+        let mut buffer = [0; 3];
+        let mut writer = Writer::new(&mut buffer);
+        writer.u16(1).unwrap();
+        assert_eq!(writer.u16(2), Err(SnapshotError::BufferFull));
+        assert_eq!(writer.len(), 2);
+        assert_eq!(writer.u8(3), Ok(()));
+    }
+
+    #[test]
+    fn truncate_drops_the_tail() {
+        let mut buffer = [0; 8];
+        let mut writer = Writer::new(&mut buffer);
+        writer.u32(1).unwrap();
+        writer.truncate(1);
+        assert_eq!(writer.len(), 1);
+        writer.truncate(5);
+        assert_eq!(writer.len(), 1);
+        writer.truncate(0);
+        assert!(writer.is_empty());
+    }
+
+    #[test]
+    fn reading_past_the_end_fails() {
+        let mut reader = Reader::new(&[1, 2, 3]);
+        assert_eq!(reader.u32(), Err(SnapshotError::UnexpectedEnd));
+        assert_eq!(reader.u16(), Ok(0x0201));
+        assert_eq!(reader.u16(), Err(SnapshotError::UnexpectedEnd));
+        assert_eq!(reader.bytes(usize::MAX), Err(SnapshotError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn bool_rejects_other_values() {
+        assert_eq!(Reader::new(&[0]).bool(), Ok(false));
+        assert_eq!(Reader::new(&[2]).bool(), Err(SnapshotError::Invalid));
+    }
+
+    #[test]
+    fn state_round_trips_with_its_version() {
+        let mut buffer = [0; 8];
+        let mut writer = Writer::new(&mut buffer);
+        save(&Counter(500), &mut writer).unwrap();
+        let len = writer.len();
+        assert_eq!(&buffer[..len], [3, 0xF4, 0x01]);
+
+        let mut counter = Counter(0);
+        restore(&mut counter, &mut Reader::new(&buffer[..len])).unwrap();
+        assert_eq!(counter, Counter(500));
+    }
+
+    #[test]
+    fn counter_passes_the_shared_checks() {
+        assert_eq!(check_snapshot(|| Counter(500)), [3, 0xF4, 0x01]);
+        assert_eq!(
+            corrupted(Counter(7), &[3, 0, 0], &[(0, &[2])]),
+            Some(SnapshotError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let mut counter = Counter(7);
+        assert_eq!(
+            restore(&mut counter, &mut Reader::new(&[4, 1, 0])),
+            Err(SnapshotError::UnsupportedVersion(4))
+        );
+        assert_eq!(
+            restore(&mut counter, &mut Reader::new(&[])),
+            Err(SnapshotError::UnexpectedEnd)
+        );
+        assert_eq!(counter, Counter(7));
+    }
+
+    #[test]
+    fn snapshot_errors_map_to_storage_errors() {
+        assert_eq!(
+            StorageError::from(SnapshotError::BufferFull),
+            StorageError::TooLarge
+        );
+        assert_eq!(
+            StorageError::from(SnapshotError::UnexpectedEnd),
+            StorageError::Corrupt
+        );
+    }
+}
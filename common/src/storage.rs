@@ -0,0 +1,92 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageError {
+    /// The record does not fit into the given buffer or into the storage itself
+    TooLarge,
+    /// The stored data is not in the expected format
+    Corrupt,
+    /// The underlying medium failed
+    Io,
+}
+
+/// System-specific persistent storage of small records, such as high scores and settings.
+/// Records are identified by a namespace (e.g. `"scores"`) and a name within it (e.g. the name of a game).
+pub trait Storage {
+    /// Reads a record into `buffer` and returns its length, or `None` if there is no such record
+    fn read(
+        &mut self,
+        namespace: &str,
+        name: &str,
+        buffer: &mut [u8],
+    ) -> Result<Option<usize>, StorageError>;
+
+    /// Stores a record, replacing any previous record with the same namespace and name
+    fn write(&mut self, namespace: &str, name: &str, data: &[u8]) -> Result<(), StorageError>;
+}
+
+/// Storage that keeps nothing, for systems without persistent storage
+pub struct NoStorage;
+
+impl Storage for NoStorage {
+    fn read(
+        &mut self,
+        _namespace: &str,
+        _name: &str,
+        _buffer: &mut [u8],
+    ) -> Result<Option<usize>, StorageError> {
+        Ok(None)
+    }
+
+    fn write(&mut self, _namespace: &str, _name: &str, _data: &[u8]) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    extern crate std;
+
+    use std::{string::String, vec::Vec};
+
+    use super::*;
+
+    /// Keeps the records in memory
+    #[derive(Default)]
+    pub(crate) struct MemoryStorage {
+        records: Vec<(String, String, Vec<u8>)>,
+        /// The number of records written so far
+        pub(crate) writes: usize,
+        /// Makes every write fail, as a worn out or unavailable medium would
+        pub(crate) failing: bool,
+    }
+
+    impl Storage for MemoryStorage {
+        fn read(
+            &mut self,
+            namespace: &str,
+            name: &str,
+            buffer: &mut [u8],
+        ) -> Result<Option<usize>, StorageError> {
+            let Some((_, _, data)) = self
+                .records
+                .iter()
+                .find(|(n, r, _)| n == namespace && r == name)
+            else {
+                return Ok(None);
+            };
+            let len = data.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&data[..len]);
+            Ok(Some(len))
+        }
+
+        fn write(&mut self, namespace: &str, name: &str, data: &[u8]) -> Result<(), StorageError> {
+            self.writes += 1;
+            if self.failing {
+                return Err(StorageError::Io);
+            }
+            self.records.retain(|(n, r, _)| n != namespace || r != name);
+            self.records
+                .push((namespace.into(), name.into(), data.into()));
+            Ok(())
+        }
+    }
+}
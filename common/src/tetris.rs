@@ -1,32 +1,38 @@
 use core::time::Duration;
 
 use crate::{
-    Game, RandomNumberSource,
+    Icon, RandomNumberSource,
     display::{Pixel, PixelDisplay},
+    events::{Cue, GameEvent, GameEventBuffer},
+    grid::{Grid, MAX_CELLS},
     input::Input,
+    settings::Difficulty,
+    shell::{Outcome, PlayLoop},
+    snapshot::{Reader, Snapshot, SnapshotError, Writer},
 };
 
+/// Version of the snapshot format, see [`Snapshot`]
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// Time between the steps of a new round at the normal difficulty
+const START_RATE: Duration = Duration::from_millis(400);
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum BoardState {
     Free,
     Occupied,
 }
-pub struct TetrisGame<const ROWS: usize, const COLS: usize> {
-    state: State,
+pub struct TetrisGame {
+    game_over: bool,
     update_timer: Duration,
     update_rate: Duration,
-    board: [[BoardState; COLS]; ROWS],
+    difficulty: Difficulty,
+    board: Grid<BoardState>,
 
     score: usize,
-    state_wait_timer: Duration,
     current: Option<Tetronomicon>,
-}
-
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum State {
-    PreStart,
-    Running,
-    GameOver,
+    /// True when the board or the current piece changed since it was last rendered
+    dirty: bool,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -37,8 +43,10 @@ enum Type {
     Line,
 }
 impl Type {
+    const ALL: [Type; 4] = [Type::Square, Type::L, Type::T, Type::Line];
+
     fn new_random(rng: &mut impl RandomNumberSource) -> Self {
-        match rng.next_u32() % 4 {
+        match rng.next_range(0..4) {
             0 => Self::Square,
             1 => Self::L,
             2 => Self::T,
@@ -66,8 +74,10 @@ enum Rotation {
 }
 
 impl Rotation {
+    const ALL: [Rotation; 4] = [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270];
+
     fn new_random(rng: &mut impl RandomNumberSource) -> Self {
-        match rng.next_u32() % 4 {
+        match rng.next_range(0..4) {
             0 => Self::R0,
             1 => Self::R90,
             2 => Self::R180,
@@ -112,12 +122,13 @@ struct Tetronomicon {
     column: isize,
 }
 impl Tetronomicon {
-    fn new_random(rng: &mut impl RandomNumberSource) -> Self {
+    /// A random piece above the middle of a board with the given number of columns
+    fn new_random(rng: &mut impl RandomNumberSource, columns: usize) -> Self {
         Self {
             kind: Type::new_random(rng),
             rotation: Rotation::new_random(rng),
             row: -4, // start above the screen
-            column: 8,
+            column: columns as isize / 2,
         }
     }
 
@@ -145,25 +156,41 @@ impl Tetronomicon {
 //                 {
 // }
 
-impl<const ROWS: usize, const COLS: usize> TetrisGame<ROWS, COLS> {
+impl TetrisGame {
+    /// A game without a board, it gets sized by [`PlayLoop::fit`]
     pub fn new() -> Self {
         Self {
-            state: State::PreStart,
+            game_over: false,
             update_timer: Duration::ZERO,
-            update_rate: Duration::from_millis(400),
-            board: [[BoardState::Free; COLS]; ROWS],
+            update_rate: START_RATE,
+            difficulty: Difficulty::Normal,
+            board: Grid::new(BoardState::Free),
             score: 0,
-            state_wait_timer: Duration::ZERO,
             current: None,
+            dirty: true,
         }
     }
 
+    pub fn with_difficulty(difficulty: Difficulty) -> Self {
+        Self {
+            update_rate: difficulty.scale(START_RATE),
+            difficulty,
+            ..Self::new()
+        }
+    }
+
+    /// Starts a new round on an empty board of the given size
+    fn start(&mut self, rows: usize, columns: usize) {
+        *self = Self::with_difficulty(self.difficulty);
+        self.board.resize(rows, columns, BoardState::Free);
+    }
+
     // Check if a Tetronomicon does not go outide the edges of the field and not collide with any other occupied cell
-    fn is_valid(t: &Tetronomicon, board: &[[BoardState; COLS]; ROWS]) -> bool {
+    fn is_valid(t: &Tetronomicon, board: &Grid<BoardState>) -> bool {
         // first make sure all cells are within the bounds
         for (row, col) in t.cells() {
             // left right and bottom, not checking the top (since they all start at the top)
-            if row >= ROWS as isize || col < 0 || col >= COLS as isize {
+            if row >= board.rows() as isize || col < 0 || col >= board.columns() as isize {
                 return false;
             }
         }
@@ -171,7 +198,7 @@ impl<const ROWS: usize, const COLS: usize> TetrisGame<ROWS, COLS> {
         // make sure no parts of the board is occupied
         for (row, col) in t.cells() {
             // left right and bottom, not checking the top (since they all start at the top)
-            if row >= 0 && board[row as usize][col as usize] == BoardState::Occupied {
+            if row >= 0 && board.get(row as usize, col as usize) == BoardState::Occupied {
                 return false;
             }
         }
@@ -180,7 +207,7 @@ impl<const ROWS: usize, const COLS: usize> TetrisGame<ROWS, COLS> {
     }
 
     /// Move the current block down one row and run all the logic
-    fn move_down(&mut self, rng: &mut impl RandomNumberSource) {
+    fn move_down(&mut self, rng: &mut impl RandomNumberSource, events: &mut GameEventBuffer) {
         if let Some(t) = &mut self.current {
             t.row += 1;
 
@@ -191,83 +218,162 @@ impl<const ROWS: usize, const COLS: usize> TetrisGame<ROWS, COLS> {
 
                 // if we collided above the top, then game is over
                 if t.row <= 0 {
-                    self.state = State::GameOver;
+                    self.game_over = true;
                 }
 
                 self.score += 1;
 
                 for (row, col) in t.cells() {
-                    if row >= 0 && row < ROWS as isize && col >= 0 && col < COLS as isize {
-                        self.board[row as usize][col as usize] = BoardState::Occupied;
+                    if self.board.contains(row, col) {
+                        self.board
+                            .set(row as usize, col as usize, BoardState::Occupied);
                     }
                 }
 
                 // run the "fall down" algorithm to remove full rows (start from top)
-                for row in 0..=(ROWS - 1) {
-                    let row_full =
-                        (0..COLS - 1).all(|col| self.board[row][col] == BoardState::Occupied);
+                let mut cleared = 0;
+                for row in 0..self.board.rows() {
+                    let row_full = (0..self.board.columns() - 1)
+                        .all(|col| self.board.get(row, col) == BoardState::Occupied);
 
                     if !row_full {
                         continue;
                     }
 
                     // row full, move cells down!
-                    for r in (0..=(row - 1)).rev() {
-                        self.board[r + 1] = self.board[r];
+                    for r in (0..row).rev() {
+                        self.board.copy_row(r, r + 1);
                     }
 
                     // accumulate points!
                     self.score += 10;
+                    cleared += 1;
                 }
 
-                *t = Tetronomicon::new_random(rng);
+                events.push(GameEvent::ScoreChanged(self.score));
+                if cleared > 0 {
+                    events.push(GameEvent::LineCleared(cleared));
+                    events.push(GameEvent::SoundCue(Cue::Clear));
+                }
+
+                *t = Tetronomicon::new_random(rng, self.board.columns());
             }
         }
     }
 }
 
-impl<const ROWS: usize, const COLS: usize> Default for TetrisGame<ROWS, COLS> {
+impl Default for TetrisGame {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<const ROWS: usize, const COLS: usize, I: Input, D: PixelDisplay, R: RandomNumberSource>
-    Game<I, D, R> for TetrisGame<ROWS, COLS>
-{
-    fn update(&mut self, elapsed: Duration, input: &I, display: &mut D, random: &mut R) {
-        if self.state == State::PreStart {
-            display.clear();
-            display.draw_text(0, 0, "RDY");
+/// Stores the board size, timers and score, the current piece (if any) and the board packed into
+/// bits, row by row
+impl Snapshot for TetrisGame {
+    fn version(&self) -> u8 {
+        SNAPSHOT_VERSION
+    }
+
+    fn save(&self, writer: &mut Writer) -> Result<(), SnapshotError> {
+        writer.u16(self.board.rows() as u16)?;
+        writer.u16(self.board.columns() as u16)?;
+        writer.duration(self.update_timer)?;
+        writer.duration(self.update_rate)?;
+        writer.u32(u32::try_from(self.score).unwrap_or(u32::MAX))?;
 
-            display.draw_text(8, COLS as isize / 2 - 3, "T");
+        writer.bool(self.current.is_some())?;
+        if let Some(t) = &self.current {
+            writer.u8(t.kind as u8)?;
+            writer.u8(t.rotation as u8)?;
+            writer.i16(t.row as i16)?;
+            writer.i16(t.column as i16)?;
+        }
 
-            // delay for starting the game
-            self.state_wait_timer += elapsed;
+        let mut bits = 0u8;
+        for (i, cell) in self.board.cells().iter().enumerate() {
+            if *cell == BoardState::Occupied {
+                bits |= 1 << (i % 8);
+            }
+            if i % 8 == 7 {
+                writer.u8(bits)?;
+                bits = 0;
+            }
+        }
+        if !self.board.cells().len().is_multiple_of(8) {
+            writer.u8(bits)?;
+        }
+        Ok(())
+    }
 
-            if input.action() && self.state_wait_timer > Duration::from_millis(1000) {
-                // moving on, reset the timer (for use by the game over state)
-                self.state_wait_timer = Duration::ZERO;
+    fn restore(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+        let rows = reader.u16()? as usize;
+        let columns = reader.u16()? as usize;
+        if rows * columns > MAX_CELLS || columns == 0 {
+            return Err(SnapshotError::Invalid);
+        }
 
-                self.state = State::Running;
+        let mut game = Self::new();
+        game.difficulty = self.difficulty;
+        game.board.resize(rows, columns, BoardState::Free);
+        game.update_timer = reader.duration()?;
+        game.update_rate = reader.duration()?;
+        game.score = reader.u32()? as usize;
+
+        if reader.bool()? {
+            let kind = *Type::ALL
+                .get(reader.u8()? as usize)
+                .ok_or(SnapshotError::Invalid)?;
+            let rotation = *Rotation::ALL
+                .get(reader.u8()? as usize)
+                .ok_or(SnapshotError::Invalid)?;
+            game.current = Some(Tetronomicon {
+                kind,
+                rotation,
+                row: reader.i16()? as isize,
+                column: reader.i16()? as isize,
+            });
+        }
+
+        let mut bits = 0u8;
+        for (i, cell) in game.board.cells_mut().iter_mut().enumerate() {
+            if i % 8 == 0 {
+                bits = reader.u8()?;
             }
-            return;
-        } else if self.state == State::GameOver {
-            display.clear();
-            display.draw_text(0, 0, "DEAD");
-            display.draw_text(8, 0, "=");
-            display.draw_number(8, 10, self.score);
-
-            // delay for leaving the game over state
-            self.state_wait_timer += elapsed;
-
-            if input.action() && self.state_wait_timer > Duration::from_millis(1000) {
-                *self = TetrisGame::new(); // restart by reinstantiating self ;)
+            if bits & (1 << (i % 8)) != 0 {
+                *cell = BoardState::Occupied;
             }
+        }
+
+        if !Difficulty::scaled_range(START_RATE).contains(&game.update_rate)
+            || game.update_timer >= game.update_rate
+            || game
+                .current
+                .as_ref()
+                .is_some_and(|t| !Self::is_valid(t, &game.board))
+        {
+            return Err(SnapshotError::Invalid);
+        }
 
-            return;
-        } // else continue with the game logic
+        *self = game;
+        Ok(())
+    }
+}
+
+impl<I: Input, R: RandomNumberSource> PlayLoop<I, R> for TetrisGame {
+    fn fit(&mut self, rows: usize, columns: usize) {
+        if !self.board.fits(rows, columns) {
+            self.start(rows, columns);
+        }
+    }
 
+    fn play(
+        &mut self,
+        elapsed: Duration,
+        input: &I,
+        random: &mut R,
+        events: &mut GameEventBuffer,
+    ) -> Outcome {
         self.update_timer += elapsed;
 
         // always let the user rotate the block
@@ -295,27 +401,37 @@ impl<const ROWS: usize, const COLS: usize, I: Input, D: PixelDisplay, R: RandomN
             }
 
             if input.down() {
-                self.move_down(random);
+                self.move_down(random, events);
+            }
+
+            if input.action() || input.left() || input.right() || input.down() {
+                self.dirty = true;
             }
         }
 
-        if self.update_timer > self.update_rate {
+        while self.update_timer >= self.update_rate && !self.game_over {
             self.update_timer -= self.update_rate;
 
+            self.dirty = true;
             if self.current.is_none() {
-                self.current = Some(Tetronomicon::new_random(random))
+                self.current = Some(Tetronomicon::new_random(random, self.board.columns()))
             }
-            self.move_down(random);
+            self.move_down(random, events);
         }
 
-        // redraw
+        if self.game_over {
+            Outcome::GameOver
+        } else {
+            Outcome::Continue
+        }
+    }
+
+    fn render(&mut self, display: &mut dyn PixelDisplay) {
         display.clear();
 
-        for r in 0..ROWS {
-            for c in 0..COLS {
-                if self.board[r][c] == BoardState::Occupied {
-                    display.set_pixel(r, c, Pixel::On);
-                }
+        for (r, c, cell) in self.board.iter() {
+            if cell == BoardState::Occupied {
+                display.set_pixel(r, c, Pixel::On);
             }
         }
 
@@ -331,13 +447,152 @@ impl<const ROWS: usize, const COLS: usize, I: Input, D: PixelDisplay, R: RandomN
                 }
             }
         }
+        self.dirty = false;
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn name(&self) -> &'static str {
+        "TETRIS"
+    }
+
+    fn icon(&self) -> Icon {
+        Icon::new(&[0x0C, 0x0C, 0x00, 0x00, 0x20, 0x61, 0xFB, 0xFF], 8)
+    }
+
+    fn score(&self) -> usize {
+        self.score
+    }
+
+    fn reset(&mut self) {
+        self.start(self.board.rows(), self.board.columns());
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        Some(self.difficulty)
+    }
+
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.update_rate = difficulty.scale(START_RATE);
+    }
+
+    fn snapshot(&self) -> Option<&dyn Snapshot> {
+        Some(self)
+    }
+
+    fn snapshot_mut(&mut self) -> Option<&mut dyn Snapshot> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input::BasicInput,
+        random::Pcg32,
+        snapshot::tests::{check_snapshot, corrupted, restored, saved},
+    };
+
+    /// A game on a 12x8 board with a piece falling and a few pieces landed
+    fn played() -> TetrisGame {
+        let mut game = TetrisGame::new();
+        let mut random = Pcg32::new(7);
+        PlayLoop::<BasicInput, Pcg32>::fit(&mut game, 12, 8);
+        let input = BasicInput {
+            down: true,
+            ..Default::default()
+        };
+        for _ in 0..30 {
+            let outcome = game.play(
+                Duration::from_millis(150),
+                &input,
+                &mut random,
+                &mut GameEventBuffer::new(),
+            );
+            assert!(matches!(outcome, Outcome::Continue));
+        }
+        assert!(game.current.is_some());
+        assert!(game.board.cells().contains(&BoardState::Occupied));
+        game
+    }
+
+    /// Offsets into a snapshot, after the version byte
+    const TIMER: usize = 5;
+    const RATE: usize = 9;
+    const HAS_PIECE: usize = 17;
+    const KIND: usize = 18;
+    const PIECE_ROW: usize = 20;
+    const PIECE_COLUMN: usize = 22;
+
+    #[test]
+    fn snapshot_round_trips() {
+        let game = played();
+        let data = check_snapshot(played);
+        assert_eq!(data.len(), 24 + 12);
+
+        let restored = restored(TetrisGame::new(), &data).unwrap();
+        assert_eq!(restored.score, game.score);
+        assert_eq!(restored.board.cells(), game.board.cells());
     }
 
-    fn state(&self) -> crate::GameState {
-        match self.state {
-            State::PreStart => crate::GameState::Start,
-            State::Running => crate::GameState::Playing,
-            State::GameOver => crate::GameState::GameOver,
+    #[test]
+    fn snapshot_keeps_the_rate_of_every_difficulty() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let mut game = played();
+            PlayLoop::<BasicInput, Pcg32>::set_difficulty(&mut game, difficulty);
+            game.update_timer = Duration::ZERO;
+
+            let restored = restored(played(), &saved(&game)).unwrap();
+            assert_eq!(restored.update_rate, difficulty.scale(START_RATE));
         }
     }
+
+    #[test]
+    fn corrupt_snapshots_are_rejected() {
+        let data = check_snapshot(played);
+        let corrupt = |patches: &[(usize, &[u8])]| corrupted(played(), &data, patches);
+        let millis = |ms: u32| ms.to_le_bytes();
+
+        // board larger than a grid holds, or without columns
+        assert_eq!(corrupt(&[(1, &[0, 1, 0, 1])]), Some(SnapshotError::Invalid));
+        assert_eq!(corrupt(&[(3, &[0, 0])]), Some(SnapshotError::Invalid));
+        // no such piece
+        assert_eq!(corrupt(&[(HAS_PIECE, &[2])]), Some(SnapshotError::Invalid));
+        assert_eq!(corrupt(&[(KIND, &[4])]), Some(SnapshotError::Invalid));
+        // piece outside of the board
+        assert_eq!(
+            corrupt(&[(PIECE_ROW, &12i16.to_le_bytes())]),
+            Some(SnapshotError::Invalid)
+        );
+        assert_eq!(
+            corrupt(&[(PIECE_COLUMN, &(-5i16).to_le_bytes())]),
+            Some(SnapshotError::Invalid)
+        );
+        // step rates no difficulty reaches, including zero which would never finish a step
+        let range = Difficulty::scaled_range(START_RATE);
+        let fastest = range.start().as_millis() as u32;
+        let slowest = range.end().as_millis() as u32;
+        assert_eq!(corrupt(&[(RATE, &millis(0))]), Some(SnapshotError::Invalid));
+        assert_eq!(
+            corrupt(&[(RATE, &millis(fastest - 1))]),
+            Some(SnapshotError::Invalid)
+        );
+        assert_eq!(
+            corrupt(&[(RATE, &millis(slowest + 1))]),
+            Some(SnapshotError::Invalid)
+        );
+        // a step that is overdue
+        assert_eq!(
+            corrupt(&[(TIMER, &millis(400))]),
+            Some(SnapshotError::Invalid)
+        );
+
+        let no_time = (TIMER, &millis(0)[..]);
+        assert_eq!(corrupt(&[no_time, (RATE, &millis(fastest))]), None);
+        assert_eq!(corrupt(&[no_time, (RATE, &millis(slowest))]), None);
+    }
 }
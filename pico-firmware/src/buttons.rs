@@ -0,0 +1,132 @@
+//! Interrupt driven reading of the buttons, so that even short taps are turned into input events with
+//! accurate timestamps no matter how long the main loop spends refreshing the display. The contacts
+//! are debounced, so each press and release produces a single event.
+
+use core::{cell::RefCell, time::Duration};
+
+use crate::bsp::hal::{
+    Timer,
+    gpio::{DynPinId, FunctionSioInput, Interrupt, Pin, PullUp},
+    pac::{self, interrupt},
+};
+use common::input::{Button, InputEvent, InputEventBuffer, InputEventKind};
+use cortex_m::interrupt::Mutex;
+use embedded_hal::digital::InputPin;
+
+/// The number of events that can be queued between two updates of the main loop
+pub const QUEUE_SIZE: usize = 32;
+
+/// The number of players with their own set of buttons
+pub const PLAYERS: usize = 2;
+
+pub type ButtonPin = Pin<DynPinId, FunctionSioInput, PullUp>;
+
+/// The buttons of a single player
+pub type PlayerPins = [(Button, ButtonPin); 5];
+
+/// Changes of a button within this time of its last accepted change are contact bounce
+const DEBOUNCE: Duration = Duration::from_millis(5);
+
+struct Buttons {
+    pins: [PlayerPins; PLAYERS],
+    /// The accepted state (pressed or not) of each button and the time it last changed
+    states: [[(bool, Duration); 5]; PLAYERS],
+    timer: Timer,
+}
+
+impl Buttons {
+    /// Queues an event for every button whose level differs from its accepted state, unless the state
+    /// changed within [`DEBOUNCE`]. Deciding by the level rather than by the edges keeps the order of
+    /// a release and a quick re-press, and a level that settles after an ignored bounce is picked up
+    /// by the next call.
+    fn sample(&mut self, events: &mut [InputEventBuffer<QUEUE_SIZE>; PLAYERS]) {
+        let now = Duration::from_micros(self.timer.get_counter().ticks());
+
+        for ((pins, states), events) in self
+            .pins
+            .iter_mut()
+            .zip(self.states.iter_mut())
+            .zip(events.iter_mut())
+        {
+            for ((button, pin), (pressed, changed)) in pins.iter_mut().zip(states.iter_mut()) {
+                // the buttons pull the pin low when pressed
+                let level = pin.is_low().unwrap_or(*pressed);
+                if level == *pressed || now.saturating_sub(*changed) < DEBOUNCE {
+                    continue;
+                }
+
+                *pressed = level;
+                *changed = now;
+                let kind = if level {
+                    InputEventKind::Pressed
+                } else {
+                    InputEventKind::Released
+                };
+                if !events.push(InputEvent {
+                    button: *button,
+                    kind,
+                    timestamp: now,
+                }) {
+                    defmt::warn!("input event queue full, dropping event");
+                }
+            }
+        }
+    }
+}
+
+static BUTTONS: Mutex<RefCell<Option<Buttons>>> = Mutex::new(RefCell::new(None));
+static EVENTS: Mutex<RefCell<[InputEventBuffer<QUEUE_SIZE>; PLAYERS]>> =
+    Mutex::new(RefCell::new([InputEventBuffer::new(); PLAYERS]));
+
+/// Enables the edge interrupts of the (active low) button pins and hands them to the interrupt handler
+pub fn init(mut pins: [PlayerPins; PLAYERS], timer: Timer) {
+    // buttons held during start-up count as pressed from the start, without an event
+    let states = core::array::from_fn(|player| {
+        core::array::from_fn(|i| (pins[player][i].1.is_low().unwrap_or(false), Duration::ZERO))
+    });
+
+    for (_, pin) in pins.iter_mut().flatten() {
+        pin.set_interrupt_enabled(Interrupt::EdgeLow, true);
+        pin.set_interrupt_enabled(Interrupt::EdgeHigh, true);
+    }
+
+    cortex_m::interrupt::free(|cs| {
+        BUTTONS.borrow(cs).replace(Some(Buttons {
+            pins,
+            states,
+            timer,
+        }));
+    });
+
+    // SAFETY: the handler only accesses state protected by the critical section mutexes above
+    unsafe {
+        pac::NVIC::unmask(pac::Interrupt::IO_IRQ_BANK0);
+    }
+}
+
+/// Removes and returns all events of each player that happened since the previous call
+pub fn take_events() -> [InputEventBuffer<QUEUE_SIZE>; PLAYERS] {
+    cortex_m::interrupt::free(|cs| {
+        let mut events = EVENTS.borrow(cs).borrow_mut();
+        if let Some(buttons) = BUTTONS.borrow(cs).borrow_mut().as_mut() {
+            buttons.sample(&mut events);
+        }
+        core::array::from_fn(|player| events[player].take())
+    })
+}
+
+#[interrupt]
+fn IO_IRQ_BANK0() {
+    cortex_m::interrupt::free(|cs| {
+        let mut buttons = BUTTONS.borrow(cs).borrow_mut();
+        let Some(buttons) = buttons.as_mut() else {
+            return;
+        };
+
+        for (_, pin) in buttons.pins.iter_mut().flatten() {
+            pin.clear_interrupt(Interrupt::EdgeLow);
+            pin.clear_interrupt(Interrupt::EdgeHigh);
+        }
+        buttons.sample(&mut EVENTS.borrow(cs).borrow_mut());
+    });
+}
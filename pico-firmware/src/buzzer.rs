@@ -0,0 +1,76 @@
+//! Sound effects on a piezo buzzer driven by a PWM square wave.
+//!
+//! The buzzer is connected to GPIO15, which is channel B of PWM slice 7. The frequency is set via the
+//! wrap value of the slice, the duty cycle stays at 50% while a tone plays and 0% for silence.
+
+use core::time::Duration;
+
+use common::audio::{AudioSink, Sequence, SequencePlayer};
+use embedded_hal::pwm::SetDutyCycle;
+
+use crate::bsp::hal::{
+    gpio::{AnyPin, bank0::Gpio15},
+    pwm::{FreeRunning, Pwm7, Slice},
+};
+
+/// Divides the system clock down so the audible frequencies fit into the 16 bit counter
+const CLOCK_DIVIDER: u8 = 64;
+
+pub struct Buzzer {
+    slice: Slice<Pwm7, FreeRunning>,
+    /// Rate of the PWM counter in Hz
+    counter_hz: u32,
+    player: SequencePlayer,
+    /// The frequency currently output, zero for silence
+    frequency: u16,
+}
+
+impl Buzzer {
+    pub fn new<P: AnyPin<Id = Gpio15>>(
+        mut slice: Slice<Pwm7, FreeRunning>,
+        pin: P,
+        system_clock_hz: u32,
+    ) -> Self {
+        slice.set_div_int(CLOCK_DIVIDER);
+        slice.channel_b.output_to(pin);
+        let _ = slice.channel_b.set_duty_cycle(0);
+        slice.enable();
+
+        Self {
+            slice,
+            counter_hz: system_clock_hz / CLOCK_DIVIDER as u32,
+            player: SequencePlayer::new(),
+            frequency: 0,
+        }
+    }
+
+    fn set_frequency(&mut self, frequency: u16) {
+        if frequency == self.frequency {
+            return;
+        }
+        self.frequency = frequency;
+
+        if frequency == 0 {
+            let _ = self.slice.channel_b.set_duty_cycle(0);
+            return;
+        }
+
+        // frequencies too low for the counter are played as the lowest one possible
+        let top = (self.counter_hz / frequency as u32)
+            .saturating_sub(1)
+            .clamp(1, u16::MAX as u32) as u16;
+        self.slice.set_top(top);
+        let _ = self.slice.channel_b.set_duty_cycle(top / 2);
+    }
+}
+
+impl AudioSink for Buzzer {
+    fn play(&mut self, sequence: Sequence) {
+        self.player.start(sequence);
+    }
+
+    fn update(&mut self, elapsed: Duration) {
+        let frequency = self.player.update(elapsed);
+        self.set_frequency(frequency);
+    }
+}
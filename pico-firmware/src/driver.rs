@@ -1,5 +1,9 @@
 use core::convert::Infallible;
 
+use common::{
+    display::{Pixel, PixelDisplay},
+    framebuffer::Framebuffer,
+};
 use cortex_m::delay::Delay;
 use embedded_hal::digital::OutputPin;
 
@@ -138,25 +142,25 @@ impl Pins<'_> {
 
 pub struct Display<'a, const ROWS: usize, const COLS: usize> {
     pins: Pins<'a>,
-    buffer_shadow: [[bool; COLS]; ROWS],
-    buffer_active: [[bool; COLS]; ROWS],
+    buffer_shadow: Framebuffer<ROWS, COLS>,
+    buffer_active: Framebuffer<ROWS, COLS>,
 }
 
-impl<const ROWS: usize, const COLS: usize> common::display::PixelDisplay
-    for Display<'_, ROWS, COLS>
-{
-    const ROWS: usize = ROWS;
-    const COLUMNS: usize = COLS;
-
-    fn set_pixel(&mut self, row: usize, col: usize, value: common::display::Pixel) {
-        self.set_pixel(
-            row,
-            col,
-            match value {
-                common::display::Pixel::On => true,
-                common::display::Pixel::Off => false,
-            },
-        )
+impl<const ROWS: usize, const COLS: usize> PixelDisplay for Display<'_, ROWS, COLS> {
+    fn rows(&self) -> usize {
+        ROWS
+    }
+
+    fn columns(&self) -> usize {
+        COLS
+    }
+
+    fn set_pixel(&mut self, row: usize, col: usize, value: Pixel) {
+        self.buffer_active.set_pixel(row, col, value);
+    }
+
+    fn fill(&mut self, value: Pixel) {
+        self.buffer_active.fill(value);
     }
 }
 
@@ -167,47 +171,31 @@ impl<'a, const ROWS: usize, const COLS: usize> Display<'a, ROWS, COLS> {
 
         Display {
             pins,
-            buffer_shadow: [[false; COLS]; ROWS],
-            buffer_active: [[false; COLS]; ROWS],
+            buffer_shadow: Framebuffer::new(),
+            buffer_active: Framebuffer::new(),
         }
     }
 
-    pub fn clear(&mut self) {
-        self.fill(false);
-    }
-
-    pub fn fill(&mut self, value: bool) {
-        for row in 0..ROWS {
-            for col in 0..COLS {
-                self.buffer_active[row][col] = value;
+    pub fn refresh(&mut self, delay: &mut Delay, force_refresh: bool) {
+        if force_refresh {
+            for (row, col, value) in self.buffer_active.pixels() {
+                self.pins.drive_pixel(row, col, value.into(), delay, 200);
+            }
+        } else {
+            for (row, col, value) in self.buffer_active.changes(&self.buffer_shadow) {
+                self.pins.drive_pixel(row, col, value.into(), delay, 200);
             }
         }
-    }
 
-    pub fn set_pixel(&mut self, row: usize, col: usize, value: bool) {
-        self.buffer_active[row][col] = value;
+        self.buffer_shadow.copy_from(&self.buffer_active);
     }
+}
 
-    pub fn refresh(&mut self, delay: &mut Delay, force_refresh: bool) {
-        for row in 0..ROWS {
-            for col in 0..COLS {
-                if force_refresh || self.buffer_active[row][col] != self.buffer_shadow[row][col] {
-                    // need to update the display pixel
-                    self.pins.drive_pixel(
-                        row,
-                        col,
-                        if self.buffer_active[row][col] {
-                            DriveDirection::Low
-                        } else {
-                            DriveDirection::High
-                        },
-                        delay,
-                        200,
-                    );
-
-                    self.buffer_shadow[row][col] = self.buffer_active[row][col];
-                }
-            }
+impl From<Pixel> for DriveDirection {
+    fn from(value: Pixel) -> Self {
+        match value {
+            Pixel::On => DriveDirection::Low,
+            Pixel::Off => DriveDirection::High,
         }
     }
 }
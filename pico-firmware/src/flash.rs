@@ -0,0 +1,225 @@
+//! Persistent storage in the last sector of the flash. All records share the one 4K sector:
+//!
+//! - `scores`: the high score table of each game, about 40 bytes per game
+//! - `settings`: the system settings and the difficulty of each game, a few bytes each
+//! - `saves`: the round saved when a game was paused, up to
+//!   [`MAX_SAVE_SIZE`](common::menu::MAX_SAVE_SIZE) (2K) bytes
+//!
+//! The sector is mirrored in RAM and holds a magic number followed by the records, each stored as
+//! `[namespace length: u8][name length: u8][data length: u16 LE][namespace][name][data]`. So a
+//! namespace is at most 254 bytes long (255 marks the erased rest of the sector), a name at most 255
+//! and the data at most 65535, and all records together including their 4 byte headers have to fit
+//! into the sector after the magic number. A write that does not fit fails with
+//! [`StorageError::TooLarge`]. Every write erases and programs the whole sector again, so the records
+//! are only written when they changed.
+
+use common::storage::{Storage, StorageError};
+
+use crate::bsp::hal::rom_data;
+
+/// Size of the flash chip on the Pico
+const FLASH_SIZE: usize = 2048 * 1024;
+
+/// Smallest unit the flash can erase
+const SECTOR_SIZE: usize = 4096;
+
+/// Offset of the storage sector from the start of the flash, it is excluded from `FLASH` in `memory.x`
+const STORAGE_OFFSET: usize = FLASH_SIZE - SECTOR_SIZE;
+
+/// Address the flash is mapped to for reading (execute in place)
+const XIP_BASE: usize = 0x1000_0000;
+
+/// Size of the second stage bootloader at the start of the flash
+const BOOT2_SIZE: usize = 256;
+
+/// The 4K sector erase command of the flash chip
+const SECTOR_ERASE_COMMAND: u8 = 0x20;
+
+const MAGIC: [u8; 4] = *b"FDS1";
+const RECORD_HEADER_SIZE: usize = 4;
+const ERASED: u8 = 0xFF;
+
+/// A record in the mirror, with the offsets of its parts
+struct Record {
+    start: usize,
+    data: usize,
+    end: usize,
+}
+
+pub struct FlashStorage {
+    mirror: [u8; SECTOR_SIZE],
+}
+
+impl FlashStorage {
+    /// Reads the storage sector, starting with an empty storage if it was never written
+    pub fn new() -> Self {
+        // SAFETY: the sector is not part of the program (see memory.x) and always readable through XIP
+        let stored = unsafe {
+            core::slice::from_raw_parts((XIP_BASE + STORAGE_OFFSET) as *const u8, SECTOR_SIZE)
+        };
+
+        let mut mirror = [ERASED; SECTOR_SIZE];
+        if stored.starts_with(&MAGIC) {
+            mirror.copy_from_slice(stored);
+        } else {
+            mirror[..MAGIC.len()].copy_from_slice(&MAGIC);
+        }
+
+        Self { mirror }
+    }
+
+    /// Returns the record starting at `start`, `None` past the last record
+    fn record_at(&self, start: usize) -> Option<Record> {
+        let header = self.mirror.get(start..start + RECORD_HEADER_SIZE)?;
+        if header[0] == ERASED {
+            return None;
+        }
+
+        let keys = header[0] as usize + header[1] as usize;
+        let data = start + RECORD_HEADER_SIZE + keys;
+        let end = data + u16::from_le_bytes([header[2], header[3]]) as usize;
+        (end <= SECTOR_SIZE).then_some(Record { start, data, end })
+    }
+
+    fn find(&self, namespace: &str, name: &str) -> Option<Record> {
+        let mut start = MAGIC.len();
+        while let Some(record) = self.record_at(start) {
+            let namespace_len = self.mirror[start] as usize;
+            let keys = start + RECORD_HEADER_SIZE;
+            if &self.mirror[keys..keys + namespace_len] == namespace.as_bytes()
+                && &self.mirror[keys + namespace_len..record.data] == name.as_bytes()
+            {
+                return Some(record);
+            }
+            start = record.end;
+        }
+        None
+    }
+
+    /// Offset just after the last record
+    fn end(&self) -> usize {
+        let mut start = MAGIC.len();
+        while let Some(record) = self.record_at(start) {
+            start = record.end;
+        }
+        start
+    }
+}
+
+impl Default for FlashStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Storage for FlashStorage {
+    fn read(
+        &mut self,
+        namespace: &str,
+        name: &str,
+        buffer: &mut [u8],
+    ) -> Result<Option<usize>, StorageError> {
+        let Some(record) = self.find(namespace, name) else {
+            return Ok(None);
+        };
+
+        let data = &self.mirror[record.data..record.end];
+        let target = buffer.get_mut(..data.len()).ok_or(StorageError::TooLarge)?;
+        target.copy_from_slice(data);
+        Ok(Some(data.len()))
+    }
+
+    fn write(&mut self, namespace: &str, name: &str, data: &[u8]) -> Result<(), StorageError> {
+        if namespace.len() >= ERASED as usize
+            || name.len() > u8::MAX as usize
+            || data.len() > u16::MAX as usize
+        {
+            return Err(StorageError::TooLarge);
+        }
+
+        // remove the previous version of the record by moving all following records over it
+        let mut end = self.end();
+        if let Some(record) = self.find(namespace, name) {
+            self.mirror.copy_within(record.end..end, record.start);
+            end -= record.end - record.start;
+            self.mirror[end..].fill(ERASED);
+        }
+
+        let length = RECORD_HEADER_SIZE + namespace.len() + name.len() + data.len();
+        if end + length > SECTOR_SIZE {
+            return Err(StorageError::TooLarge);
+        }
+
+        let record = &mut self.mirror[end..end + length];
+        record[0] = namespace.len() as u8;
+        record[1] = name.len() as u8;
+        record[2..4].copy_from_slice(&(data.len() as u16).to_le_bytes());
+        let (keys, payload) =
+            record[RECORD_HEADER_SIZE..].split_at_mut(namespace.len() + name.len());
+        keys[..namespace.len()].copy_from_slice(namespace.as_bytes());
+        keys[namespace.len()..].copy_from_slice(name.as_bytes());
+        payload.copy_from_slice(data);
+
+        program_sector(&self.mirror);
+        Ok(())
+    }
+}
+
+/// The ROM functions needed to program the flash, looked up in advance because the lookup code runs
+/// from the flash which is unavailable while it is being programmed
+struct FlashFunctions {
+    connect_internal_flash: unsafe extern "C" fn(),
+    flash_exit_xip: unsafe extern "C" fn(),
+    flash_range_erase: unsafe extern "C" fn(u32, usize, u32, u8),
+    flash_range_program: unsafe extern "C" fn(u32, *const u8, usize),
+    flash_flush_cache: unsafe extern "C" fn(),
+}
+
+/// Erases the storage sector and programs it with `data`
+fn program_sector(data: &[u8; SECTOR_SIZE]) {
+    // the bootloader sets up the fast XIP mode again after programming, so keep a copy in RAM
+    let mut boot2 = [0u32; BOOT2_SIZE / 4];
+    // SAFETY: both buffers are word aligned and BOOT2_SIZE bytes long
+    unsafe {
+        rom_data::memcpy44(
+            boot2.as_mut_ptr(),
+            XIP_BASE as *const u32,
+            BOOT2_SIZE as u32,
+        );
+    }
+
+    let functions = FlashFunctions {
+        connect_internal_flash: rom_data::connect_internal_flash::ptr(),
+        flash_exit_xip: rom_data::flash_exit_xip::ptr(),
+        flash_range_erase: rom_data::flash_range_erase::ptr(),
+        flash_range_program: rom_data::flash_range_program::ptr(),
+        flash_flush_cache: rom_data::flash_flush_cache::ptr(),
+    };
+
+    // nothing may run from the flash in the meantime, including interrupt handlers
+    cortex_m::interrupt::free(|_| {
+        // SAFETY: the function and all data it uses are in RAM, and the sector is not part of the program
+        unsafe { write_sector(&functions, boot2.as_ptr(), data.as_ptr()) }
+    });
+}
+
+#[inline(never)]
+#[unsafe(link_section = ".data.ram_func")]
+unsafe fn write_sector(functions: &FlashFunctions, boot2: *const u32, data: *const u8) {
+    unsafe {
+        (functions.connect_internal_flash)();
+        (functions.flash_exit_xip)();
+        (functions.flash_range_erase)(
+            STORAGE_OFFSET as u32,
+            SECTOR_SIZE,
+            SECTOR_SIZE as u32,
+            SECTOR_ERASE_COMMAND,
+        );
+        (functions.flash_range_program)(STORAGE_OFFSET as u32, data, SECTOR_SIZE);
+        (functions.flash_flush_cache)();
+
+        // the bootloader is thumb code, so its address needs the lowest bit set
+        let boot2: extern "C" fn() = core::mem::transmute((boot2 as *const u8).add(1));
+        boot2();
+    }
+}
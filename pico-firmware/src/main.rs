@@ -4,14 +4,17 @@
 #![no_std]
 #![no_main]
 
+mod buttons;
+mod buzzer;
 mod driver;
+mod flash;
 
 use core::time::Duration;
 
 use bsp::entry;
 use defmt::*;
 use defmt_rtt as _;
-use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal::digital::OutputPin;
 use panic_probe as _;
 
 // Provide an alias for our BSP so we can switch targets quickly.
@@ -19,13 +22,26 @@ use panic_probe as _;
 use rp_pico as bsp;
 // use sparkfun_pro_micro_rp2040 as bsp;
 use common::{
-    Game, RandomNumberSource, input::DebouncedInput, menu::GameMenu, snake::SnakeGame,
+    Game,
+    audio::AudioSink,
+    display::{Pixel, PixelDisplay},
+    game_loop::{GameLoop, TimeSource},
+    input::{Button, Controller, Input, Players},
+    menu::GameMenu,
+    orientation::{Orientation, OrientedGame, Rotation},
+    random::Pcg32,
+    settings::RefreshMode,
+    shell::GameShell,
+    snake::SnakeGame,
     tetris::TetrisGame,
 };
 
 use bsp::hal::{
+    adc::Adc,
     clocks::{Clock, init_clocks_and_plls},
     pac,
+    pwm::Slices,
+    rosc::RingOscillator,
     sio::Sio,
     watchdog::Watchdog,
 };
@@ -103,7 +119,7 @@ fn main() -> ! {
     led_pin.set_high().unwrap();
     // do a deep screen refresh (to make sure all pixels are really off)
     display.clear();
-    display.fill(true);
+    display.fill(Pixel::On);
     display.refresh(&mut delay, true);
     delay.delay_ms(500);
 
@@ -115,63 +131,188 @@ fn main() -> ! {
 
     led_pin.set_low().unwrap();
 
+    // Tetris is played in portrait, with the device turned counterclockwise
     let mut games = [
-        &mut TetrisGame::<ROWS, COLS>::new() as &mut dyn Game<_, _, _>,
-        &mut SnakeGame::<ROWS, COLS>::new() as &mut dyn Game<_, _, _>,
+        &mut OrientedGame::new(
+            GameShell::new(TetrisGame::new()),
+            Orientation::new(Rotation::R90),
+        ) as &mut dyn Game<_, _>,
+        &mut GameShell::new(SnakeGame::new()) as &mut dyn Game<_, _>,
     ];
 
     let mut game = GameMenu::new(&mut games);
 
-    let mut input_up = pins.gpio28.into_pull_up_input();
-    let mut input_down = pins.gpio27.into_pull_up_input();
-    let mut input_left = pins.gpio26.into_pull_up_input();
-    let mut input_right = pins.gpio22.into_pull_up_input();
-    let mut input_action = pins.gpio21.into_pull_up_input();
+    let mut storage = flash::FlashStorage::new();
+    if let Err(e) = game.load_high_scores(&mut storage) {
+        warn!("could not load the high scores: {}", Debug2Format(&e));
+    }
+    if let Err(e) = game.load_settings(&mut storage) {
+        warn!("could not load the settings: {}", Debug2Format(&e));
+    }
+    match game.resume_saved_game(&mut storage) {
+        Ok(true) => info!("Continuing the saved {} game", game.name()),
+        Ok(false) => {}
+        Err(e) => warn!("could not continue the saved game: {}", Debug2Format(&e)),
+    }
+
+    let pwm_slices = Slices::new(pac.PWM, &mut pac.RESETS);
+    let mut buzzer = buzzer::Buzzer::new(
+        pwm_slices.pwm7,
+        pins.gpio15,
+        clocks.system_clock.freq().to_Hz(),
+    );
 
-    let mut i_debounced = DebouncedInput::default();
+    let timer = bsp::hal::Timer::new(pac.TIMER, &mut pac.RESETS, &clocks);
 
-    let mut rng = RandomSource::default();
+    // the buttons generate input events from an interrupt handler
+    buttons::init(
+        [
+            // player 1
+            [
+                (Button::Up, pins.gpio28.into_pull_up_input().into_dyn_pin()),
+                (
+                    Button::Down,
+                    pins.gpio27.into_pull_up_input().into_dyn_pin(),
+                ),
+                (
+                    Button::Left,
+                    pins.gpio26.into_pull_up_input().into_dyn_pin(),
+                ),
+                (
+                    Button::Right,
+                    pins.gpio22.into_pull_up_input().into_dyn_pin(),
+                ),
+                (
+                    Button::Action,
+                    pins.gpio21.into_pull_up_input().into_dyn_pin(),
+                ),
+            ],
+            // player 2
+            [
+                (Button::Up, pins.gpio18.into_pull_up_input().into_dyn_pin()),
+                (
+                    Button::Down,
+                    pins.gpio19.into_pull_up_input().into_dyn_pin(),
+                ),
+                (
+                    Button::Left,
+                    pins.gpio20.into_pull_up_input().into_dyn_pin(),
+                ),
+                (
+                    Button::Right,
+                    pins.gpio0.into_pull_up_input().into_dyn_pin(),
+                ),
+                (
+                    Button::Action,
+                    pins.gpio1.into_pull_up_input().into_dyn_pin(),
+                ),
+            ],
+        ],
+        timer,
+    );
+
+    let mut players = Players::<Controller, { buttons::PLAYERS }>::default();
+
+    let seed = entropy_seed(pac.ROSC, pac.ADC, &mut pac.RESETS);
+    info!("Using random seed {}", seed);
+    let mut rng = Pcg32::new(seed);
+    let mut paused = false;
+    let mut game_loop = GameLoop::new(TimerClock(timer));
     loop {
-        // tick every 10ms
-        delay.delay_ms(10);
-
-        // refresh screen if game did an update
-        let elapsed = Duration::from_millis(10);
-
-        // read the input
-        let i = common::input::BasicInput {
-            left: input_left.is_low().unwrap(),
-            right: input_right.is_low().unwrap(),
-            up: input_up.is_low().unwrap(),
-            down: input_down.is_low().unwrap(),
-            action: input_action.is_low().unwrap(),
-        };
-
-        defmt::debug!(
-            "left:{}, right:{}, up:{}, down:{}, action: {}",
-            i.left,
-            i.right,
-            i.up,
-            i.down,
-            i.action
-        );
-
-        i_debounced.update(&i);
-
-        // the display is already "double buffered" so this repeated calling should be fine!
-        game.update(elapsed, &i_debounced, &mut display, &mut rng);
-        display.refresh(&mut delay, false);
+        // sleep until the next tick, the time the display refresh took is already accounted for
+        delay.delay_us(game_loop.until_next_tick().as_micros() as u32);
+        let ticks = game_loop.advance();
+        if ticks == 0 {
+            continue;
+        }
+
+        let tick = game_loop.tick_duration();
+        for _ in 0..ticks {
+            // apply the input events that happened since the last tick
+            let events = buttons::take_events();
+            for (player, (controller, events)) in players.iter_mut().zip(events.iter()).enumerate()
+            {
+                for event in events.as_slice() {
+                    defmt::debug!("player {}: {}", player, Debug2Format(event));
+                }
+                controller.update(events.as_slice(), tick);
+                for gesture in controller.gestures() {
+                    defmt::debug!("player {}: {}", player, Debug2Format(gesture));
+                }
+            }
+
+            game.update(tick, &players, &display, &mut rng);
+            for event in game.events() {
+                defmt::debug!("{}", Debug2Format(event));
+            }
+            if game.settings().sound {
+                buzzer.play_events(game.events());
+            }
+            buzzer.update(tick);
+        }
+
+        if let Err(e) = game.save_high_scores(&mut storage) {
+            warn!("could not save the high scores: {}", Debug2Format(&e));
+        }
+        if let Err(e) = game.save_settings(&mut storage) {
+            warn!("could not save the settings: {}", Debug2Format(&e));
+        }
+
+        // there is no telling when the power is cut, so save the game whenever it gets paused, and
+        // remove it again once the round goes on or ends. Saving on every update would wear out the
+        // flash.
+        if game.is_paused() && !paused {
+            if let Err(e) = game.save_game(&mut storage) {
+                warn!("could not save the game: {}", Debug2Format(&e));
+            }
+        }
+        paused = game.is_paused();
+        if let Err(e) = game.discard_saved_game(&mut storage) {
+            warn!("could not remove the saved game: {}", Debug2Format(&e));
+        }
+
+        // only scan the display when something moved, unless every dot should be driven every time
+        let full = game.settings().refresh == RefreshMode::Full;
+        let dirty = game.is_dirty();
+        if dirty {
+            game.render(&mut display);
+        }
+        if dirty || full {
+            display.refresh(&mut delay, full);
+        }
     }
 }
 
-#[derive(Default)]
-struct RandomSource {
-    count: u32,
+/// The microsecond timer of the RP2040, the same clock that timestamps the button events
+struct TimerClock(bsp::hal::Timer);
+
+impl TimeSource for TimerClock {
+    fn now(&self) -> Duration {
+        Duration::from_micros(self.0.get_counter().ticks())
+    }
 }
 
-impl RandomNumberSource for RandomSource {
-    fn next_u32(&mut self) -> u32 {
-        self.count += 1;
-        self.count
+/// Gathers a seed for the random number generator from two physical noise sources: the random bit
+/// of the ring oscillator and the least significant bits of the (noisy) internal temperature sensor.
+fn entropy_seed(rosc: pac::ROSC, adc: pac::ADC, resets: &mut pac::RESETS) -> u64 {
+    let rosc = RingOscillator::new(rosc).initialize();
+
+    let mut adc = Adc::new(adc, resets);
+    let mut temperature_sensor = adc.take_temp_sensor().unwrap();
+    let mut fifo = adc
+        .build_fifo()
+        .set_channel(&mut temperature_sensor)
+        .start();
+
+    let mut seed = 0u64;
+    for _ in 0..64 {
+        while fifo.len() == 0 {}
+        let noise = fifo.read() as u64 & 0b11;
+
+        // rotate so the ADC noise is spread over the whole word
+        seed = seed.rotate_left(5) ^ (rosc.get_random_bit() as u64) ^ (noise << 1);
     }
+    fifo.stop();
+
+    seed
 }